@@ -0,0 +1,90 @@
+//! Synchronous versions of the api requests for programs that do not run an async runtime. The
+//! functions take the same arguments as their async counterparts in requests.rs. They drive the
+//! request on an internal single threaded tokio runtime, so they must not be called from within
+//! an async context.
+use crate::{error::*, *};
+
+use std::sync::OnceLock;
+use tokio::runtime::{Builder, Runtime};
+
+static RUNTIME: OnceLock<Runtime> = OnceLock::new();
+
+// The runtime is kept for the whole program since the connection pool of the client lives on it
+fn runtime() -> Result<&'static Runtime> {
+    if let Some(runtime) = RUNTIME.get() {
+        return Ok(runtime);
+    }
+    let runtime = Builder::new_current_thread().enable_all().build()?;
+    Ok(RUNTIME.get_or_init(|| runtime))
+}
+
+/// Blocking version of requests::get_replays()
+pub fn get_replays<A, B, C, D, E>(
+    context: &Context,
+    platform: Platform,
+    pages: usize,
+    replays_per_page: usize,
+    request_parameters: QueryParameters<A, B, C, D, E>,
+) -> Result<(
+    impl DoubleEndedIterator<Item = Match>,
+    impl Iterator<Item = ParseError>,
+)> {
+    runtime()?.block_on(requests::get_replays(
+        context,
+        platform,
+        pages,
+        replays_per_page,
+        request_parameters,
+    ))
+}
+
+/// Blocking version of requests::get_replays_page()
+pub fn get_replays_page<A, B, C, D, E>(
+    context: &Context,
+    platform: Platform,
+    page: usize,
+    replays_per_page: usize,
+    request_parameters: &QueryParameters<A, B, C, D, E>,
+) -> Result<(
+    impl DoubleEndedIterator<Item = Match>,
+    impl Iterator<Item = ParseError>,
+)> {
+    runtime()?.block_on(requests::get_replays_page(
+        context,
+        platform,
+        page,
+        replays_per_page,
+        request_parameters,
+    ))
+}
+
+/// Blocking version of requests::get_user()
+pub fn get_user(context: &Context, platform: Platform, user_id: i64) -> Result<User> {
+    runtime()?.block_on(requests::get_user(context, platform, user_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::requests::tests::{http_response, serve, RESPONSE_2};
+
+    #[test]
+    fn blocking_get_replays() {
+        // The server needs its own runtime that keeps running between the blocking calls
+        let server = Runtime::new().unwrap();
+        let url = server.block_on(serve(vec![
+            http_response("200 OK", RESPONSE_2),
+            http_response("200 OK", RESPONSE_2),
+        ]));
+
+        let ctx = Context::new(url);
+        let (matches, errors) =
+            get_replays(&ctx, Platform::PC, 1, 10, QueryParameters::default()).unwrap();
+        assert_eq!(matches.count(), 10);
+        assert_eq!(errors.count(), 0);
+
+        let (matches, _) =
+            get_replays_page(&ctx, Platform::PC, 0, 10, &QueryParameters::default()).unwrap();
+        assert_eq!(matches.count(), 10);
+    }
+}
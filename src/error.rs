@@ -1,39 +1,113 @@
 use std::{
     error,
     fmt::{self, Display},
+    time::Duration,
 };
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum Error {
     ReqwestError(reqwest::Error),
+    /// The request took longer than the timeout of the context. The source is the reqwest error,
+    /// with the url and the kind of timeout, or the elapsed error of a custom Transport.
+    Timeout(Box<dyn error::Error + Send + Sync>),
+    /// The server answered with 503 Service Unavailable or a maintenance page instead of an api
+    /// response, which it does during maintenance
+    ServerUnavailable,
+    /// The server answered with 429 Too Many Requests. `retry_after` is the wait it asked for in
+    /// the Retry-After header, if it sent one.
+    RateLimited {
+        retry_after: Option<Duration>,
+    },
     ChronoParseError(chrono::ParseError),
     ParsingBytesError(&'static str),
     UnexpectedResponse(&'static str),
     InvalidCharacterCode(&'static str),
+    /// The string passed to Character::from_code, FromStr or TryFrom is not a known character
+    InvalidCharacterName(String),
     InvalidArgument(String),
     InvalidMessagePack(rmp_serde::decode::Error),
+    /// Decoding a response failed after reading `offset` of its `length` bytes
+    Parse {
+        offset: usize,
+        length: usize,
+        reason: rmp_serde::decode::Error,
+    },
+    InvalidReplay(Box<ParseError>),
+    IoError(std::io::Error),
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
 
+impl Error {
+    /// Whether the error may go away by repeating the request. This is the case for network
+    /// errors, timeouts, rate limiting and server errors but not for invalid arguments or
+    /// responses that cannot be parsed.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            // Errors without a status never got a response, e.g. failed connections
+            Error::ReqwestError(e) => match e.status() {
+                Some(status) => status.is_server_error(),
+                None => !e.is_builder(),
+            },
+            Error::Timeout(_) | Error::ServerUnavailable | Error::RateLimited { .. } => true,
+            _ => false,
+        }
+    }
+
+    /// The http status of the response that caused the error, if there was one
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            Error::ReqwestError(e) => e.status().map(|s| s.as_u16()),
+            Error::ServerUnavailable => Some(503),
+            Error::RateLimited { .. } => Some(429),
+            _ => None,
+        }
+    }
+}
+
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Error::ReqwestError(e) => write!(f, "Error making request: {}", e),
+            Error::Timeout(_) => write!(f, "Request timed out"),
+            Error::ServerUnavailable => {
+                write!(f, "Server unavailable, it may be under maintenance")
+            }
+            Error::RateLimited {
+                retry_after: Some(retry_after),
+            } => write!(f, "Rate limited, retry after {:?}", retry_after),
+            Error::RateLimited { retry_after: None } => write!(f, "Rate limited"),
             Error::ChronoParseError(e) => write!(f, "Error parsing datetime: {}", e),
             Error::ParsingBytesError(msg) => write!(f, "{}", msg),
             Error::UnexpectedResponse(msg) => {
                 write!(f, "Unexpected response from API, {}", msg)
             }
             Error::InvalidCharacterCode(code) => write!(f, "{} is not valid character code", code),
+            Error::InvalidCharacterName(name) => write!(f, "{} is not a known character", name),
             Error::InvalidArgument(msg) => write!(f, "Invalid argument: {}", msg),
             Error::InvalidMessagePack(msg) => write!(f, "Invalid messagepack: {}", msg),
+            Error::Parse {
+                offset,
+                length,
+                reason,
+            } => write!(
+                f,
+                "Invalid messagepack at byte {} of {}: {}",
+                offset, length, reason
+            ),
+            Error::InvalidReplay(e) => write!(f, "{}", e),
+            Error::IoError(e) => write!(f, "Error writing output: {}", e),
         }
     }
 }
 
 impl From<reqwest::Error> for Error {
     fn from(e: reqwest::Error) -> Self {
-        Error::ReqwestError(e)
+        if e.is_timeout() {
+            Error::Timeout(Box::new(e))
+        } else {
+            Error::ReqwestError(e)
+        }
     }
 }
 
@@ -49,12 +123,38 @@ impl From<rmp_serde::decode::Error> for Error {
     }
 }
 
-impl error::Error for Error {}
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Error::IoError(e)
+    }
+}
+
+impl From<ParseError> for Error {
+    fn from(e: ParseError) -> Self {
+        Error::InvalidReplay(Box::new(e))
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Error::ReqwestError(e) => Some(e),
+            Error::Timeout(e) => Some(e.as_ref()),
+            Error::ChronoParseError(e) => Some(e),
+            Error::InvalidMessagePack(e) => Some(e),
+            Error::Parse { reason, .. } => Some(reason),
+            Error::InvalidReplay(e) => Some(e.as_ref()),
+            Error::IoError(e) => Some(e),
+            _ => None,
+        }
+    }
+}
 
 #[derive(Debug)]
 pub struct ParseError {
     reply_content: String,
     inner: Error,
+    index: Option<usize>,
 }
 
 impl ParseError {
@@ -62,18 +162,44 @@ impl ParseError {
         ParseError {
             reply_content,
             inner,
+            index: None,
         }
     }
+
+    /// Tag the error with the position of the replay in its page
+    pub fn with_index(self, index: usize) -> Self {
+        ParseError {
+            index: Some(index),
+            ..self
+        }
+    }
+
+    /// The zero based position of the replay in the page it came from, None if the whole response
+    /// could not be parsed
+    pub fn index(&self) -> Option<usize> {
+        self.index
+    }
 }
 
 impl Display for ParseError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(
-            f,
-            "Could not parse replay: {}\n  bytes: {}",
-            self.inner, self.reply_content
-        )
+        match self.index {
+            Some(index) => write!(
+                f,
+                "Could not parse replay {}: {}\n  bytes: {}",
+                index, self.inner, self.reply_content
+            ),
+            None => write!(
+                f,
+                "Could not parse replay: {}\n  bytes: {}",
+                self.inner, self.reply_content
+            ),
+        }
     }
 }
 
-impl error::Error for ParseError {}
+impl error::Error for ParseError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        Some(&self.inner)
+    }
+}
@@ -0,0 +1,155 @@
+use crate::{error::*, *};
+
+use std::io::Write;
+
+/// A match flattened into one owned row, e.g. for data frames or database tables. Created with
+/// Match::to_record().
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate")
+)]
+pub struct MatchRecord {
+    pub timestamp: DateTime<Utc>,
+    pub floor: Floor,
+    pub p1_id: i64,
+    pub p1_name: String,
+    pub p1_char: Character,
+    pub p2_id: i64,
+    pub p2_name: String,
+    pub p2_char: Character,
+    pub winner_side: Winner,
+}
+
+/// Write the matches as csv with a header row and one row per match. Floors are written as
+/// printed by Display (`"Floor 7"` or `"Celestial"`), characters by their three letter code from
+/// Character::to_code() (`"UNK"` for unknown characters), the winner as `"Player1"` or
+/// `"Player2"` and the timestamp in RFC 3339. These names do not change with the Debug output of
+/// the types.
+pub fn matches_to_csv<W: Write>(matches: &[Match], mut writer: W) -> Result<()> {
+    writeln!(
+        writer,
+        "timestamp,floor,player1_id,player1_name,player1_character,\
+         player2_id,player2_name,player2_character,winner"
+    )?;
+    for record in matches.iter().map(Match::to_record) {
+        let winner = match record.winner_side {
+            Winner::Player1 => "Player1",
+            Winner::Player2 => "Player2",
+        };
+        writeln!(
+            writer,
+            "{},{},{},{},{},{},{},{},{}",
+            record.timestamp.to_rfc3339(),
+            record.floor,
+            record.p1_id,
+            csv_field(&record.p1_name),
+            record.p1_char.to_code(),
+            record.p2_id,
+            csv_field(&record.p2_name),
+            record.p2_char.to_code(),
+            winner,
+        )?;
+    }
+    Ok(())
+}
+
+/// Write the matches as JSON Lines, one serialized match per line. Every line is flushed once it
+/// is written, so the output stays valid up to the last complete match if writing is
+/// interrupted. Matches are consumed one by one, e.g. straight from requests::replay_stream().
+#[cfg(feature = "serde")]
+pub fn write_matches_jsonl<W: Write>(
+    matches: impl IntoIterator<Item = Match>,
+    mut writer: W,
+) -> Result<()> {
+    for m in matches {
+        serde_json::to_writer(&mut writer, &m).map_err(std::io::Error::from)?;
+        writer.write_all(b"\n")?;
+        writer.flush()?;
+    }
+    Ok(())
+}
+
+// Quote a field if it contains a separator, quote or line break
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::test_match;
+
+    // A name that has to be quoted in csv
+    fn quoted_match() -> Match {
+        let mut m = test_match(
+            (1, Character::Sol),
+            (2, Character::HappyChaos),
+            Winner::Player2,
+        );
+        m.players.0.name = "comma, \"quote\"".into();
+        m.players.1.name = "plain".into();
+        m
+    }
+
+    #[test]
+    fn record() {
+        expect_test::expect![[r#"
+            MatchRecord {
+                timestamp: 2022-02-06T04:07:59Z,
+                floor: Celestial,
+                p1_id: 1,
+                p1_name: "comma, \"quote\"",
+                p1_char: Sol,
+                p2_id: 2,
+                p2_name: "plain",
+                p2_char: HappyChaos,
+                winner_side: Player2,
+            }
+        "#]]
+        .assert_debug_eq(&quoted_match().to_record());
+    }
+
+    #[test]
+    fn csv() {
+        let mut buf = vec![];
+        matches_to_csv(&[quoted_match()], &mut buf).unwrap();
+        expect_test::expect![[r#"
+            timestamp,floor,player1_id,player1_name,player1_character,player2_id,player2_name,player2_character,winner
+            2022-02-06T04:07:59+00:00,Celestial,1,"comma, ""quote""",SOL,2,plain,COS,Player2
+        "#]]
+        .assert_eq(&String::from_utf8(buf).unwrap());
+    }
+
+    #[test]
+    fn csv_row() {
+        let mut m = quoted_match();
+        m.floor = Floor::F7;
+        m.players.1.character = Character::Unknown(0x16);
+        m.winner = Winner::Player1;
+        let mut buf = vec![];
+        matches_to_csv(&[m], &mut buf).unwrap();
+        let csv = String::from_utf8(buf).unwrap();
+        assert_eq!(
+            csv.lines().nth(1).unwrap(),
+            "2022-02-06T04:07:59+00:00,Floor 7,1,\"comma, \"\"quote\"\"\",SOL,2,plain,UNK,Player1"
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn jsonl() {
+        let mut buf = vec![];
+        write_matches_jsonl(vec![quoted_match(), quoted_match()], &mut buf).unwrap();
+        expect_test::expect![[r#"
+            {"timestamp":"2022-02-06T04:07:59Z","floor":"Celestial","players":[{"id":1,"character":"Sol","name":"comma, \"quote\""},{"id":2,"character":"HappyChaos","name":"plain"}],"winner":"Player2"}
+            {"timestamp":"2022-02-06T04:07:59Z","floor":"Celestial","players":[{"id":1,"character":"Sol","name":"comma, \"quote\""},{"id":2,"character":"HappyChaos","name":"plain"}],"winner":"Player2"}
+        "#]]
+        .assert_eq(&String::from_utf8(buf).unwrap());
+    }
+}
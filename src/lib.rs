@@ -1,18 +1,28 @@
+#[cfg(feature = "blocking")]
+pub mod blocking;
 pub mod error;
+pub mod export;
+pub mod prelude;
 pub mod requests;
+pub mod serde_code;
+pub mod stats;
 
 use chrono::prelude::*;
 use derivative::*;
 use error::*;
 use serde_crate::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fmt;
 use std::marker::PhantomData;
+use std::str::FromStr;
 
-// Reexport the functions and structs from requests.rs and parameters.rs
+// Reexport the functions and structs from requests.rs, export.rs and stats.rs
+pub use export::*;
 pub use requests::*;
+pub use stats::*;
 
 /// Player information associated with a match
-#[derive(Derivative, Clone, Debug, PartialOrd, Ord)]
+#[derive(Derivative, Clone, Debug)]
 #[cfg_attr(
     feature = "serde",
     derive(Serialize, Deserialize),
@@ -26,6 +36,22 @@ pub struct Player {
     pub name: String,
 }
 
+impl Player {
+    /// The numeric user id of the player. The API sends it as a string of digits which is parsed
+    /// on receipt, so it is always a valid integer.
+    pub fn id(&self) -> i64 {
+        self.id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn character(&self) -> Character {
+        self.character
+    }
+}
+
 impl PartialEq for Player {
     fn eq(&self, other: &Self) -> bool {
         self.id == other.id && self.character == other.character
@@ -34,6 +60,19 @@ impl PartialEq for Player {
 
 impl Eq for Player {}
 
+// Ordering ignores the name like PartialEq does
+impl PartialOrd for Player {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Player {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        (self.id, self.character).cmp(&(other.id, other.character))
+    }
+}
+
 impl fmt::Display for Player {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{} as {}", self.name, self.character)
@@ -54,6 +93,10 @@ pub enum Winner {
 
 /// A match received by the get_replay API
 /// Use requests::get_replays() to query for replays to get a set of this struct
+///
+/// Matches are ordered by their timestamp first. Ties are broken by floor, then by the players
+/// (id and character, like equality) and finally by the winner, so the order is total and
+/// consistent with Eq.
 #[derive(Hash, PartialEq, Eq, Debug, Clone, PartialOrd, Ord)]
 #[cfg_attr(
     feature = "serde",
@@ -61,8 +104,10 @@ pub enum Winner {
     serde(crate = "serde_crate")
 )]
 pub struct Match {
+    /// Serialized as an RFC 3339 string in UTC, e.g. `"2022-02-06T04:07:59Z"`
     pub timestamp: DateTime<Utc>,
     pub floor: Floor,
+    /// Player 1 (left side) and player 2 (right side), in the order the server reports them
     pub players: (Player, Player),
     pub winner: Winner,
 }
@@ -72,14 +117,118 @@ impl Match {
         self.floor
     }
 
+    /// When the match was played, in UTC. The server reports it in Japan Standard Time.
     pub fn timestamp(&self) -> &DateTime<Utc> {
         &self.timestamp
     }
 
+    /// The timestamp converted to the local time zone of the system
+    pub fn timestamp_local(&self) -> DateTime<Local> {
+        self.timestamp.with_timezone(&Local)
+    }
+
+    /// The timestamp converted to the given time zone, e.g. a chrono::FixedOffset
+    pub fn timestamp_in<Tz: TimeZone>(&self, tz: &Tz) -> DateTime<Tz> {
+        self.timestamp.with_timezone(tz)
+    }
+
+    /// The time since the match was played, measured against Utc::now(), so the result depends
+    /// on when it is called. It is negative for the few replays with timestamps in the future.
+    pub fn age(&self) -> chrono::Duration {
+        Utc::now() - self.timestamp
+    }
+
+    /// Whether the match was played at most `within` ago, see age(). Matches with timestamps in
+    /// the future count as recent.
+    pub fn is_recent(&self, within: chrono::Duration) -> bool {
+        self.age() <= within
+    }
+
+    /// Both players as (player 1, player 2). Player 1 started on the left side.
     pub fn players(&self) -> (&Player, &Player) {
         (&self.players.0, &self.players.1)
     }
 
+    /// The player on the left side
+    pub fn player1(&self) -> &Player {
+        &self.players.0
+    }
+
+    /// The player on the right side
+    pub fn player2(&self) -> &Player {
+        &self.players.1
+    }
+
+    /// Whether either player played `character`. Use QueryParameters::character() to filter on the
+    /// server instead when possible.
+    pub fn involves_character(&self, character: Character) -> bool {
+        self.players.0.character == character || self.players.1.character == character
+    }
+
+    /// Flatten the match into a MatchRecord
+    pub fn to_record(&self) -> MatchRecord {
+        let (p1, p2) = self.players();
+        MatchRecord {
+            timestamp: self.timestamp,
+            floor: self.floor,
+            p1_id: p1.id,
+            p1_name: p1.name.clone(),
+            p1_char: p1.character,
+            p2_id: p2.id,
+            p2_name: p2.name.clone(),
+            p2_char: p2.character,
+            winner_side: self.winner,
+        }
+    }
+
+    /// Whether the user with id `id` played in the match
+    pub fn contains_player(&self, id: i64) -> bool {
+        self.players.0.id == id || self.players.1.id == id
+    }
+
+    /// The opponent of the user with id `id`, or None if the user did not play in the match
+    pub fn opponent_of(&self, id: i64) -> Option<&Player> {
+        if self.players.0.id == id {
+            Some(&self.players.1)
+        } else if self.players.1.id == id {
+            Some(&self.players.0)
+        } else {
+            None
+        }
+    }
+
+    /// Whether both matches are the same game regardless of which side each player is on, i.e.
+    /// they have the same timestamp and the same players with the same characters. Eq instead
+    /// compares the players by side. The api is not known to swap sides between requests, so
+    /// both agree for matches coming from this crate.
+    pub fn same_game(&self, other: &Match) -> bool {
+        let players = |m: &Match| {
+            let (p1, p2) = m.players();
+            let key = |p: &Player| (p.id, p.character);
+            (key(p1).min(key(p2)), key(p1).max(key(p2)))
+        };
+        self.timestamp == other.timestamp
+            && players(self) == players(other)
+            && self.winner().id == other.winner().id
+    }
+
+    /// Whether both players played the same character
+    pub fn is_mirror_match(&self) -> bool {
+        self.players.0.character == self.players.1.character
+    }
+
+    /// The characters of both players independent of their side, the character with the lower
+    /// byte code comes first
+    pub fn matchup(&self) -> (Character, Character) {
+        let (c1, c2) = (self.players.0.character, self.players.1.character);
+        (c1.min(c2), c1.max(c2))
+    }
+
+    /// Get the side of the player that won
+    pub fn winner_side(&self) -> Winner {
+        self.winner
+    }
+
     /// Get the player information about the winner
     pub fn winner(&self) -> &Player {
         match self.winner {
@@ -88,15 +237,24 @@ impl Match {
         }
     }
 
-    /// Get the player information about the winner
+    /// Get the player information about the loser
     pub fn loser(&self) -> &Player {
         match self.winner {
             Winner::Player1 => &self.players.1,
             Winner::Player2 => &self.players.0,
         }
     }
+
+    pub fn winner_character(&self) -> Character {
+        self.winner().character
+    }
+
+    pub fn loser_character(&self) -> Character {
+        self.loser().character
+    }
 }
 
+/// Shows the timestamp in UTC, use Match::timestamp_local() for a local time
 impl fmt::Display for Match {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
@@ -110,6 +268,106 @@ impl fmt::Display for Match {
     }
 }
 
+/// Profile of a user as returned by requests::get_user()
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate")
+)]
+pub struct User {
+    pub id: i64,
+    pub name: String,
+    pub comment: String,
+    pub total_ranked_matches: u64,
+    pub char_stats: BTreeMap<Character, Stats>,
+}
+
+impl User {
+    /// The number of ranked matches the user has played over all characters as reported by the
+    /// profile, which does not say whether Celestial matches are counted. The profile does not
+    /// contain the number of wins, so there is no overall win rate.
+    pub fn total_matches(&self) -> u64 {
+        self.total_ranked_matches
+    }
+
+    /// The statistics of `character`, None if the user has never played it
+    pub fn stats(&self, character: Character) -> Option<&Stats> {
+        self.char_stats.get(&character)
+    }
+
+    /// The character with the highest level, which grows with the number of matches played with
+    /// it. None if the user has not played any character.
+    pub fn favorite_character(&self) -> Option<Character> {
+        self.char_stats
+            .iter()
+            .max_by_key(|(_, stats)| stats.level)
+            .map(|(&c, _)| c)
+    }
+}
+
+/// Summarizes the profile with the name, id, number of ranked matches and the favorite character
+impl fmt::Display for User {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} ({}) {{\n  Ranked matches: {}\n",
+            self.name, self.id, self.total_ranked_matches
+        )?;
+        match self.favorite_character() {
+            Some(c) => writeln!(
+                f,
+                "  Favorite character: {} (level {})",
+                c, self.char_stats[&c].level
+            )?,
+            None => writeln!(f, "  Favorite character: none")?,
+        }
+        write!(f, "}}")
+    }
+}
+
+/// Statistics of a user for a single character. The profile does not contain the number of
+/// matches won or played with a character, so no win rate can be computed from it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate")
+)]
+pub struct Stats {
+    pub level: u64,
+    pub win_streak: u64,
+    pub max_win_streak: u64,
+}
+
+impl Stats {
+    /// The character level, which grows with the number of matches played with the character
+    pub fn level(&self) -> u64 {
+        self.level
+    }
+
+    /// Matches won in a row with the character up to the last match
+    pub fn win_streak(&self) -> u64 {
+        self.win_streak
+    }
+
+    /// The longest win streak with the character
+    pub fn max_win_streak(&self) -> u64 {
+        self.max_win_streak
+    }
+}
+
+/// Remove duplicate matches while keeping the order of first occurrence. Two matches are the same
+/// if they have the same timestamp, floor, players (id and character) and winner. Player names
+/// are ignored since they can change between requests.
+pub fn dedup_matches(matches: Vec<Match>) -> Vec<Match> {
+    let mut seen = std::collections::HashSet::new();
+    matches
+        .into_iter()
+        .filter(|m| seen.insert(m.clone()))
+        .collect()
+}
+
 /// Enum for characters in the game
 #[derive(Hash, Debug, PartialEq, Eq, Clone, Copy, PartialOrd, Ord, Serialize, Deserialize)]
 #[serde(crate = "serde_crate")]
@@ -136,43 +394,307 @@ pub enum Character {
     Testament,
     Bridget,
     Sin,
+    /// A character byte code this version of the crate does not know, e.g. of a character
+    /// released later, kept as it was sent by the server. Replays with such a code are kept with
+    /// this character instead of being dropped, and characters with different codes stay apart.
+    /// It is not part of Character::all() and cannot be parsed from a name or profile code.
+    ///
+    /// Replays get it for every code without a variant, which are 0x16 to 0xff for now. It cannot
+    /// be used to filter a query, requests with it fail with Error::InvalidArgument.
+    Unknown(u8),
 }
 
 impl fmt::Display for Character {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Character::Sol => write!(f, "Sol Badguy"),
-            Character::Ky => write!(f, "Ky Kiske"),
-            Character::May => write!(f, "May"),
-            Character::Axl => write!(f, "Axl Low"),
-            Character::Leo => write!(f, "Leo Whitefang"),
-            Character::Ino => write!(f, "I-no"),
-            Character::Zato => write!(f, "Zato=1"),
-            Character::Anji => write!(f, "Anji Mito"),
-            Character::Chipp => write!(f, "Chipp Zanuff"),
-            Character::Faust => write!(f, "Faust"),
-            Character::Potemkin => write!(f, "Potemkin"),
-            Character::Millia => write!(f, "Millia Rage"),
-            Character::Ramlethal => write!(f, "Ramlethal Valentine"),
-            Character::Giovanna => write!(f, "Giovanna"),
-            Character::Nagoriyuki => write!(f, "Nagoriyuki"),
-            Character::Goldlewis => write!(f, "Goldlewis Dickinson"),
-            Character::Jacko => write!(f, "Jack-o"),
-            Character::HappyChaos => write!(f, "Happy Chaos"),
-            Character::Baiken => write!(f, "Baiken"),
-            Character::Testament => write!(f, "Testament"),
-            Character::Bridget => write!(f, "Bridget"),
-            Character::Sin => write!(f, "Sin"),
+            Character::Unknown(code) => write!(f, "Unknown ({:02x})", code),
+            c => f.write_str(c.localized_name(Language::English)),
         }
     }
 }
 
+/// Languages character names are available in
+#[derive(Hash, Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Language {
+    English,
+    Japanese,
+}
+
+/// Coarse play style of a character, see Character::archetype()
+#[derive(Hash, Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Archetype {
+    /// Wins by staying in the opponent's face and mixing them up
+    Rushdown,
+    /// Controls the screen from a distance with projectiles or long range normals
+    Zoner,
+    /// Threatens big damage with command grabs up close
+    Grappler,
+    /// Sets up traps or summons and attacks around them
+    Setplay,
+    /// Solid at every range without a single dominant game plan
+    AllRounder,
+}
+
+/// A pair of characters, e.g. as a map key for matchup statistics. The order is kept as given, so
+/// `Matchup(Sol, Ky)` can mean "my Sol against their Ky". Use normalized() to treat both orders as
+/// the same matchup.
+#[derive(Hash, Debug, PartialEq, Eq, Clone, Copy, PartialOrd, Ord)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate")
+)]
+pub struct Matchup(pub Character, pub Character);
+
+impl Matchup {
+    /// The same matchup with the character of the lower Character::index() first, so that
+    /// `Matchup(Ky, Sol)` and `Matchup(Sol, Ky)` are equal after normalizing
+    pub fn normalized(self) -> Matchup {
+        if self.0.index() <= self.1.index() {
+            self
+        } else {
+            Matchup(self.1, self.0)
+        }
+    }
+
+    /// Whether both characters are the same
+    pub fn is_mirror(&self) -> bool {
+        self.0 == self.1
+    }
+}
+
+impl From<(Character, Character)> for Matchup {
+    fn from((c1, c2): (Character, Character)) -> Self {
+        Matchup(c1, c2)
+    }
+}
+
+impl fmt::Display for Matchup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} vs {}", self.0, self.1)
+    }
+}
+
 impl Character {
+    /// The full name of the character in the given language. The English name is the one used by
+    /// Display.
+    pub fn localized_name(&self, language: Language) -> &'static str {
+        match language {
+            Language::English => match self {
+                Character::Sol => "Sol Badguy",
+                Character::Ky => "Ky Kiske",
+                Character::May => "May",
+                Character::Axl => "Axl Low",
+                Character::Leo => "Leo Whitefang",
+                Character::Ino => "I-no",
+                Character::Zato => "Zato=1",
+                Character::Anji => "Anji Mito",
+                Character::Chipp => "Chipp Zanuff",
+                Character::Faust => "Faust",
+                Character::Potemkin => "Potemkin",
+                Character::Millia => "Millia Rage",
+                Character::Ramlethal => "Ramlethal Valentine",
+                Character::Giovanna => "Giovanna",
+                Character::Nagoriyuki => "Nagoriyuki",
+                Character::Goldlewis => "Goldlewis Dickinson",
+                Character::Jacko => "Jack-o",
+                Character::HappyChaos => "Happy Chaos",
+                Character::Baiken => "Baiken",
+                Character::Testament => "Testament",
+                Character::Bridget => "Bridget",
+                Character::Sin => "Sin",
+                Character::Unknown(_) => "Unknown",
+            },
+            Language::Japanese => self.name_ja(),
+        }
+    }
+
+    /// The Japanese name of the character as written in the game
+    pub fn name_ja(&self) -> &'static str {
+        match self {
+            Character::Sol => "ソル＝バッドガイ",
+            Character::Ky => "カイ＝キスク",
+            Character::May => "メイ",
+            Character::Axl => "アクセル＝ロウ",
+            Character::Leo => "レオ＝ホワイトファング",
+            Character::Ino => "イノ",
+            Character::Zato => "ザトー＝ONE",
+            Character::Anji => "御津闇慈",
+            Character::Chipp => "チップ＝ザナフ",
+            Character::Faust => "ファウスト",
+            Character::Potemkin => "ポチョムキン",
+            Character::Millia => "ミリア＝レイジ",
+            Character::Ramlethal => "ラムレザル＝ヴァレンタイン",
+            Character::Giovanna => "ジオヴァーナ",
+            Character::Nagoriyuki => "名残雪",
+            Character::Goldlewis => "ゴールドルイス＝ディキンソン",
+            Character::Jacko => "ジャック・オー",
+            Character::HappyChaos => "ハッピーケイオス",
+            Character::Baiken => "梅喧",
+            Character::Testament => "テスタメント",
+            Character::Bridget => "ブリジット",
+            Character::Sin => "シン＝キスク",
+            Character::Unknown(_) => "不明",
+        }
+    }
+
+    /// The archetype the character is commonly grouped into. This is not reported by the game,
+    /// the grouping follows the usual community descriptions of the characters (e.g. Dustloop)
+    /// and is therefore opinionated. Many characters fit more than one archetype, so only the
+    /// most prominent one is given.
+    pub fn archetype(&self) -> Archetype {
+        match self {
+            Character::Sol => Archetype::Rushdown,
+            Character::Ky => Archetype::AllRounder,
+            Character::May => Archetype::Rushdown,
+            Character::Axl => Archetype::Zoner,
+            Character::Chipp => Archetype::Rushdown,
+            Character::Potemkin => Archetype::Grappler,
+            Character::Faust => Archetype::Setplay,
+            Character::Millia => Archetype::Rushdown,
+            Character::Zato => Archetype::Setplay,
+            Character::Ramlethal => Archetype::AllRounder,
+            Character::Leo => Archetype::AllRounder,
+            Character::Nagoriyuki => Archetype::AllRounder,
+            Character::Giovanna => Archetype::Rushdown,
+            Character::Anji => Archetype::AllRounder,
+            Character::Ino => Archetype::Rushdown,
+            Character::Goldlewis => Archetype::Grappler,
+            Character::Jacko => Archetype::Setplay,
+            Character::HappyChaos => Archetype::Zoner,
+            Character::Baiken => Archetype::AllRounder,
+            Character::Testament => Archetype::Setplay,
+            Character::Bridget => Archetype::Setplay,
+            Character::Sin => Archetype::Rushdown,
+            // Nothing is known about the character, so it gets the most neutral archetype
+            Character::Unknown(_) => Archetype::AllRounder,
+        }
+    }
+
+    /// A representative RGB color of the character for user interfaces, e.g. red for Sol and blue
+    /// for Ky. There is no official palette, the colors are picked from the dominant color of
+    /// each character's design and are meant to be distinguishable, not exact. Character::Unknown
+    /// is grey.
+    pub fn theme_color(&self) -> (u8, u8, u8) {
+        match self {
+            Character::Sol => (178, 34, 34),
+            Character::Ky => (30, 90, 200),
+            Character::May => (255, 165, 0),
+            Character::Axl => (170, 40, 50),
+            Character::Chipp => (170, 180, 190),
+            Character::Potemkin => (110, 120, 80),
+            Character::Faust => (70, 110, 180),
+            Character::Millia => (230, 200, 90),
+            Character::Zato => (60, 30, 80),
+            Character::Ramlethal => (240, 240, 240),
+            Character::Leo => (200, 160, 40),
+            Character::Nagoriyuki => (150, 20, 40),
+            Character::Giovanna => (40, 160, 90),
+            Character::Anji => (30, 140, 160),
+            Character::Ino => (220, 30, 100),
+            Character::Goldlewis => (80, 110, 60),
+            Character::Jacko => (235, 100, 20),
+            Character::HappyChaos => (120, 40, 140),
+            Character::Baiken => (220, 60, 100),
+            Character::Testament => (130, 0, 30),
+            Character::Bridget => (80, 130, 220),
+            Character::Sin => (90, 140, 200),
+            Character::Unknown(_) => (128, 128, 128),
+        }
+    }
+
+    /// The season pass that added the character, or 0 for the 15 characters of the base game.
+    /// The grouping follows the season passes sold by Arc System Works, not the exact patch a
+    /// character was released in. None for Character::Unknown.
+    pub fn season(&self) -> Option<u8> {
+        match self {
+            Character::Sol
+            | Character::Ky
+            | Character::May
+            | Character::Axl
+            | Character::Chipp
+            | Character::Potemkin
+            | Character::Faust
+            | Character::Millia
+            | Character::Zato
+            | Character::Ramlethal
+            | Character::Leo
+            | Character::Nagoriyuki
+            | Character::Giovanna
+            | Character::Anji
+            | Character::Ino => Some(0),
+            Character::Goldlewis
+            | Character::Jacko
+            | Character::HappyChaos
+            | Character::Baiken
+            | Character::Testament => Some(1),
+            Character::Bridget | Character::Sin => Some(2),
+            Character::Unknown(_) => None,
+        }
+    }
+
+    /// Whether the character was part of the base game
+    pub fn is_base_roster(&self) -> bool {
+        self.season() == Some(0)
+    }
+
+    /// Whether the character's archetype is Archetype::Grappler
+    pub fn is_grappler(&self) -> bool {
+        self.archetype() == Archetype::Grappler
+    }
+
+    /// All characters ordered by their byte code
+    pub fn all() -> &'static [Character] {
+        &[
+            Character::Sol,
+            Character::Ky,
+            Character::May,
+            Character::Axl,
+            Character::Chipp,
+            Character::Potemkin,
+            Character::Faust,
+            Character::Millia,
+            Character::Zato,
+            Character::Ramlethal,
+            Character::Leo,
+            Character::Nagoriyuki,
+            Character::Giovanna,
+            Character::Anji,
+            Character::Ino,
+            Character::Goldlewis,
+            Character::Jacko,
+            Character::HappyChaos,
+            Character::Baiken,
+            Character::Testament,
+            Character::Bridget,
+            Character::Sin,
+        ]
+    }
+
+    /// Dense zero based index of the character in Character::all(), e.g. for indexing arrays of
+    /// per character statistics. Every Character::Unknown gets the index after the last
+    /// character, so arrays of `Character::all().len() + 1` entries can hold them as well.
+    pub fn index(&self) -> usize {
+        match self {
+            Character::Unknown(_) => Character::all().len(),
+            c => c.to_u8() as usize,
+        }
+    }
+
+    /// Inverse of index() for the characters in Character::all()
+    pub fn from_index(index: usize) -> Option<Character> {
+        Character::all().get(index).copied()
+    }
+
     /// Convert a byte into a Character enum.
     /// 00: Sol 01: Ky 02: May 03: Axl 04: Chipp 05: Pot 06: Faust 07: Millia
     /// 08: Zato-1 09: Ram 0a: Leo 0b: Nago 0c: Gio 0d: Anji 0e: I-No 0f: Goldlewis 10: Jack-O
+    /// 11: Happy Chaos 12: Baiken 13: Testament 14: Bridget 15: Sin
     ///
-    /// See https://github.com/optix2000/totsugeki/issues/35#issuecomment-922516535
+    /// See https://github.com/optix2000/totsugeki/issues/35#issuecomment-922516535 for the codes up
+    /// to Jack-O. The DLC characters after that are numbered in the order they were released.
+    /// Characters released after Sin are left out until their codes can be checked against a
+    /// recorded response.
     pub fn from_u8(c: u8) -> Result<Self> {
         match c {
             0x00 => Ok(Character::Sol),
@@ -207,8 +729,13 @@ impl Character {
     /// Convert a Character back to its u8 code
     /// 00: Sol 01: Ky 02: May 03: Axl 04: Chipp 05: Pot 06: Faust 07: Millia
     /// 08: Zato-1 09: Ram 0a: Leo 0b: Nago 0c: Gio 0d: Anji 0e: I-No 0f: Goldlewis 10: Jack-O
+    /// 11: Happy Chaos 12: Baiken 13: Testament 14: Bridget 15: Sin
     ///
-    /// See https://github.com/optix2000/totsugeki/issues/35#issuecomment-922516535
+    /// See https://github.com/optix2000/totsugeki/issues/35#issuecomment-922516535 for the codes up
+    /// to Jack-O. The DLC characters after that are numbered in the order they were released.
+    ///
+    /// Character::Unknown converts back to the byte it was received with, so a query for it
+    /// filters on that byte.
     pub fn to_u8(&self) -> u8 {
         match self {
             Character::Sol => 0x00,
@@ -233,11 +760,141 @@ impl Character {
             Character::Testament => 0x13,
             Character::Bridget => 0x14,
             Character::Sin => 0x15,
+            Character::Unknown(code) => *code,
         }
     }
+
+    /// The three letter code used for the character in the profile and statistics api, e.g.
+    /// `"SOL"` or `"COS"` for Happy Chaos. Codes of characters released after Baiken could not be
+    /// checked against a recorded response. Character::Unknown has no profile code and returns the
+    /// placeholder `"UNK"`, which from_code() does not accept.
+    pub fn to_code(&self) -> &'static str {
+        match self {
+            Character::Sol => "SOL",
+            Character::Ky => "KYK",
+            Character::May => "MAY",
+            Character::Axl => "AXL",
+            Character::Chipp => "CHP",
+            Character::Potemkin => "POT",
+            Character::Faust => "FAU",
+            Character::Millia => "MLL",
+            Character::Zato => "ZAT",
+            Character::Ramlethal => "RAM",
+            Character::Leo => "LEO",
+            Character::Nagoriyuki => "NAG",
+            Character::Giovanna => "GIO",
+            Character::Anji => "ANJ",
+            Character::Ino => "INO",
+            Character::Goldlewis => "GLD",
+            Character::Jacko => "JKO",
+            Character::HappyChaos => "COS",
+            Character::Baiken => "BKN",
+            Character::Testament => "TST",
+            Character::Bridget => "BGT",
+            Character::Sin => "SIN",
+            Character::Unknown(_) => "UNK",
+        }
+    }
+
+    /// A stable lower case slug for file names or urls of character assets, e.g. `"sol"` or
+    /// `"cos"` for Happy Chaos. It is the code of to_code() in lower case, so it never changes
+    /// for a character.
+    pub fn icon_slug(&self) -> String {
+        self.to_code().to_ascii_lowercase()
+    }
+
+    /// Inverse of to_code(). The code has to be upper case.
+    pub fn from_code(code: &str) -> Result<Self> {
+        match code {
+            "SOL" => Ok(Character::Sol),
+            "KYK" => Ok(Character::Ky),
+            "MAY" => Ok(Character::May),
+            "AXL" => Ok(Character::Axl),
+            "CHP" => Ok(Character::Chipp),
+            "POT" => Ok(Character::Potemkin),
+            "FAU" => Ok(Character::Faust),
+            "MLL" => Ok(Character::Millia),
+            "ZAT" => Ok(Character::Zato),
+            "RAM" => Ok(Character::Ramlethal),
+            "LEO" => Ok(Character::Leo),
+            "NAG" => Ok(Character::Nagoriyuki),
+            "GIO" => Ok(Character::Giovanna),
+            "ANJ" => Ok(Character::Anji),
+            "INO" => Ok(Character::Ino),
+            "GLD" => Ok(Character::Goldlewis),
+            "JKO" => Ok(Character::Jacko),
+            "COS" => Ok(Character::HappyChaos),
+            "BKN" => Ok(Character::Baiken),
+            "TST" => Ok(Character::Testament),
+            "BGT" => Ok(Character::Bridget),
+            "SIN" => Ok(Character::Sin),
+            _ => Err(Error::InvalidCharacterName(code.to_string())),
+        }
+    }
+}
+
+/// Convert a three letter profile code like `"ZAT"`, same as Character::from_code(). Unlike
+/// parsing, only the exact upper case codes are accepted.
+impl TryFrom<&str> for Character {
+    type Error = Error;
+
+    fn try_from(code: &str) -> Result<Self> {
+        Character::from_code(code)
+    }
 }
 
-/// Enum mapping for floors present in the game
+/// Parse a character from its full name as printed by Display, its three letter code or a common
+/// nickname like "Pot" or "Nago". Parsing is case insensitive.
+impl FromStr for Character {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let upper = s.trim().to_uppercase();
+        if let Ok(c) = Character::from_code(&upper) {
+            return Ok(c);
+        }
+        match upper.as_str() {
+            "SOL BADGUY" => Ok(Character::Sol),
+            "KY KISKE" | "KY" => Ok(Character::Ky),
+            "AXL LOW" => Ok(Character::Axl),
+            "CHIPP ZANUFF" | "CHIPP" => Ok(Character::Chipp),
+            "POTEMKIN" | "POTE" => Ok(Character::Potemkin),
+            "FAUST" => Ok(Character::Faust),
+            "MILLIA RAGE" | "MILLIA" => Ok(Character::Millia),
+            "ZATO=1" | "ZATO" | "ZATO-1" => Ok(Character::Zato),
+            "RAMLETHAL VALENTINE" | "RAMLETHAL" => Ok(Character::Ramlethal),
+            "LEO WHITEFANG" => Ok(Character::Leo),
+            "NAGORIYUKI" | "NAGO" => Ok(Character::Nagoriyuki),
+            "GIOVANNA" => Ok(Character::Giovanna),
+            "ANJI MITO" | "ANJI" => Ok(Character::Anji),
+            "I-NO" => Ok(Character::Ino),
+            "GOLDLEWIS DICKINSON" | "GOLD" | "GOLDLEWIS" => Ok(Character::Goldlewis),
+            "JACK-O" | "JACKO" => Ok(Character::Jacko),
+            "HAPPY CHAOS" | "CHAOS" => Ok(Character::HappyChaos),
+            "BAIKEN" => Ok(Character::Baiken),
+            "TESTAMENT" => Ok(Character::Testament),
+            "BRIDGET" => Ok(Character::Bridget),
+            _ => Err(Error::InvalidCharacterName(s.to_string())),
+        }
+    }
+}
+
+impl TryFrom<u8> for Character {
+    type Error = Error;
+
+    fn try_from(c: u8) -> Result<Self> {
+        Character::from_u8(c)
+    }
+}
+
+impl From<Character> for u8 {
+    fn from(c: Character) -> Self {
+        c.to_u8()
+    }
+}
+
+/// Enum mapping for floors present in the game. Floors are ordered by their progression, from F1
+/// up to Celestial which sorts highest, independent of their byte codes.
 #[derive(PartialOrd, Ord, Debug, PartialEq, Eq, Clone, Copy, Hash)]
 #[cfg_attr(
     feature = "serde",
@@ -258,7 +915,63 @@ pub enum Floor {
     Celestial,
 }
 
+impl fmt::Display for Floor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.level() {
+            Some(level) => write!(f, "Floor {}", level),
+            None => write!(f, "Celestial"),
+        }
+    }
+}
+
+/// Parse a floor from `"celestial"`, the floor number like `"10"`, or the number prefixed with
+/// `"F"` or `"Floor "` as printed by Display, e.g. `"F7"`. Parsing is case insensitive.
+impl FromStr for Floor {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let lower = s.trim().to_lowercase();
+        if lower == "celestial" {
+            return Ok(Floor::Celestial);
+        }
+        let number = lower
+            .strip_prefix("floor")
+            .or_else(|| lower.strip_prefix('f'))
+            .unwrap_or(&lower)
+            .trim_start();
+        match number.parse::<usize>() {
+            Ok(n @ 1..=10) => Ok(Floor::all()[n - 1]),
+            _ => Err(Error::InvalidArgument(format!("{} is not a floor", s))),
+        }
+    }
+}
+
 impl Floor {
+    /// All floors from floor 1 up to celestial
+    pub fn all() -> &'static [Floor] {
+        &[
+            Floor::F1,
+            Floor::F2,
+            Floor::F3,
+            Floor::F4,
+            Floor::F5,
+            Floor::F6,
+            Floor::F7,
+            Floor::F8,
+            Floor::F9,
+            Floor::F10,
+            Floor::Celestial,
+        ]
+    }
+
+    /// The number of the floor, None for celestial
+    pub fn level(&self) -> Option<u8> {
+        match self {
+            Floor::Celestial => None,
+            f => Some(f.to_u8()),
+        }
+    }
+
     /// Create a floor from a byte representation
     ///
     /// See https://github.com/optix2000/totsugeki/issues/35#issuecomment-922516535 for mapping
@@ -328,7 +1041,8 @@ pub enum NoMaxFloorSet {}
 pub enum MaxFloorSet {}
 
 /// Struct to group queryable filters together. This is passed to the get_replays function. The
-/// default query searches for all matches between floor 1 and celestial.
+/// default query searches for all matches between floor 1 and celestial. The server filters on an
+/// inclusive range of floors, so a single request covers e.g. floor 7 to floor 10.
 ///
 /// To find matches between floor 10 and celestial between Sol and Zato where Sol wins you would
 /// construct the following query parameters:
@@ -374,7 +1088,10 @@ impl Default
 }
 
 impl<A, B, C, E> QueryParameters<A, B, C, NoMinFloorSet, E> {
-    /// Set the minimum floor to query for
+    /// Set the minimum floor to query for. Matches on this floor and all floors above it, up to
+    /// the maximum floor (Celestial by default), are returned. The server filters the whole range
+    /// in a single request, so e.g. `min_floor(Floor::F7)` covers F7 to Celestial without
+    /// querying every floor.
     pub fn min_floor(self, floor: Floor) -> QueryParameters<A, B, C, MinFloorSet, E> {
         QueryParameters {
             min_floor: floor,
@@ -409,6 +1126,14 @@ impl<A, B, C, D> QueryParameters<A, B, C, D, NoMaxFloorSet> {
     }
 }
 
+impl<A, B, C> QueryParameters<A, B, C, NoMinFloorSet, NoMaxFloorSet> {
+    /// Only query for matches on a single floor, same as setting min_floor and max_floor to the
+    /// same value
+    pub fn floor(self, floor: Floor) -> QueryParameters<A, B, C, MinFloorSet, MaxFloorSet> {
+        self.min_floor(floor).max_floor(floor)
+    }
+}
+
 impl<B, C, D, E> QueryParameters<NoChar1Set, B, C, D, E> {
     /// Set the player 1 character
     pub fn character(self, character: Character) -> QueryParameters<Char1Set, B, C, D, E> {
@@ -493,6 +1218,368 @@ mod test {
         assert_eq!(p1, p2);
     }
 
+    #[test]
+    fn favorite_character() {
+        let stats = |level| Stats {
+            level,
+            win_streak: 0,
+            max_win_streak: 0,
+        };
+        let mut user = User {
+            id: 1,
+            name: "name".into(),
+            comment: "".into(),
+            total_ranked_matches: 0,
+            char_stats: BTreeMap::new(),
+        };
+        assert_eq!(user.favorite_character(), None);
+        user.char_stats.insert(Character::Sol, stats(10));
+        user.char_stats.insert(Character::Zato, stats(200));
+        user.char_stats.insert(Character::Ky, stats(30));
+        assert_eq!(user.favorite_character(), Some(Character::Zato));
+        assert_eq!(user.stats(Character::Ky).map(Stats::level), Some(30));
+        assert_eq!(user.stats(Character::May), None);
+        expect_test::expect![[r#"
+            name (1) {
+              Ranked matches: 0
+              Favorite character: Zato=1 (level 200)
+            }"#]]
+        .assert_eq(&user.to_string());
+    }
+
+    #[test]
+    fn dedup() {
+        let m = |id, name: &str| Match {
+            timestamp: Utc.with_ymd_and_hms(2022, 2, 6, 4, 7, 59).unwrap(),
+            floor: Floor::F10,
+            players: (
+                Player {
+                    id,
+                    character: Character::Sol,
+                    name: name.into(),
+                },
+                Player {
+                    id: 1,
+                    character: Character::Ky,
+                    name: "ky".into(),
+                },
+            ),
+            winner: Winner::Player1,
+        };
+        let deduped = dedup_matches(vec![m(2, "a"), m(3, "b"), m(2, "renamed")]);
+        assert_eq!(deduped, vec![m(2, "a"), m(3, "b")]);
+        assert_eq!(deduped[0].players().0.name, "a");
+    }
+
+    // A match on Celestial at 2022-02-06 04:07:59 UTC between players named after their ids,
+    // shared by the tests of all modules. Change the defaults with at() and on_floor().
+    pub(crate) fn test_match(
+        player1: (i64, Character),
+        player2: (i64, Character),
+        winner: Winner,
+    ) -> Match {
+        let player = |(id, character): (i64, Character)| Player {
+            id,
+            character,
+            name: format!("player{}", id),
+        };
+        Match {
+            timestamp: Utc.with_ymd_and_hms(2022, 2, 6, 4, 7, 59).unwrap(),
+            floor: Floor::Celestial,
+            players: (player(player1), player(player2)),
+            winner,
+        }
+    }
+
+    impl Match {
+        pub(crate) fn at(self, timestamp: DateTime<Utc>) -> Match {
+            Match { timestamp, ..self }
+        }
+
+        pub(crate) fn on_floor(self, floor: Floor) -> Match {
+            Match { floor, ..self }
+        }
+    }
+
+    #[test]
+    fn timestamp_time_zone() {
+        let m = test_match((1, Character::Sol), (2, Character::Ky), Winner::Player1)
+            .at(Utc.with_ymd_and_hms(2022, 2, 6, 23, 30, 0).unwrap());
+        let tokyo = FixedOffset::east_opt(9 * 3600).unwrap();
+        assert_eq!(
+            m.timestamp_in(&tokyo).to_string(),
+            "2022-02-07 08:30:00 +09:00"
+        );
+        assert_eq!(m.timestamp_local(), *m.timestamp());
+        assert!(m.to_string().starts_with("2022-02-06 23:30:00 UTC"));
+    }
+
+    #[test]
+    fn matchup_normalized() {
+        let sol_ky = Matchup(Character::Sol, Character::Ky);
+        let ky_sol = Matchup::from((Character::Ky, Character::Sol));
+        assert_ne!(sol_ky, ky_sol);
+        assert_eq!(ky_sol.normalized(), sol_ky);
+        assert_eq!(sol_ky.normalized(), sol_ky);
+        assert_eq!(
+            Matchup(Character::Unknown(0x16), Character::Sin).normalized(),
+            Matchup(Character::Sin, Character::Unknown(0x16))
+        );
+        assert!(Matchup(Character::May, Character::May).is_mirror());
+        assert_eq!(ky_sol.to_string(), "Ky Kiske vs Sol Badguy");
+    }
+
+    #[test]
+    fn age() {
+        let m = test_match((1, Character::Sol), (2, Character::Ky), Winner::Player1)
+            .at(Utc::now() - chrono::Duration::hours(2));
+        assert!(m.age() >= chrono::Duration::hours(2));
+        assert!(m.is_recent(chrono::Duration::days(1)));
+        assert!(!m.is_recent(chrono::Duration::hours(1)));
+    }
+
+    #[test]
+    fn opponent() {
+        let m = test_match((1, Character::Sol), (2, Character::Ky), Winner::Player2);
+        assert!(m.contains_player(2));
+        assert!(!m.contains_player(3));
+        assert_eq!(m.opponent_of(1).map(Player::id), Some(2));
+        assert_eq!(m.opponent_of(2).map(Player::id), Some(1));
+        assert_eq!(m.opponent_of(3), None);
+    }
+
+    #[test]
+    fn same_game() {
+        let timestamp = Utc.with_ymd_and_hms(2022, 2, 6, 4, 7, 59).unwrap();
+        let m = test_match((1, Character::Sol), (2, Character::Ky), Winner::Player1).at(timestamp);
+        let swapped =
+            test_match((2, Character::Ky), (1, Character::Sol), Winner::Player2).at(timestamp);
+        assert_ne!(m, swapped);
+        assert!(m.same_game(&swapped));
+        let other_winner =
+            test_match((2, Character::Ky), (1, Character::Sol), Winner::Player1).at(timestamp);
+        assert!(!m.same_game(&other_winner));
+        let later = test_match((1, Character::Sol), (2, Character::Ky), Winner::Player1)
+            .at(timestamp + chrono::Duration::seconds(1));
+        assert!(!m.same_game(&later));
+    }
+
+    #[test]
+    fn player_ord_eq() {
+        let p1 = Player {
+            id: 2,
+            character: Character::Sol,
+            name: "name1".into(),
+        };
+        let p2 = Player {
+            id: 2,
+            character: Character::Sol,
+            name: "name2".into(),
+        };
+        assert_eq!(p1.cmp(&p2), std::cmp::Ordering::Equal);
+    }
+
+    #[test]
+    fn all_characters() {
+        let codes = (0..=u8::MAX)
+            .filter(|&c| Character::from_u8(c).is_ok())
+            .count();
+        assert_eq!(Character::all().len(), codes);
+        for (i, &c) in Character::all().iter().enumerate() {
+            assert_eq!(c.to_u8() as usize, i);
+            assert_eq!(Character::try_from(u8::from(c)).unwrap(), c);
+            assert_eq!(c.index(), i);
+            assert_eq!(Character::from_index(i), Some(c));
+        }
+    }
+
+    #[test]
+    fn unknown_index() {
+        let count = Character::all().len();
+        assert_eq!(Character::Unknown(0x16).index(), count);
+        assert_eq!(Character::from_index(count), None);
+
+        let unknown = Character::Unknown(0x16);
+        assert_eq!(unknown.to_u8(), 0x16);
+        assert_ne!(unknown, Character::Unknown(0x17));
+        assert_eq!(unknown.to_string(), "Unknown (16)");
+        assert!(Character::from_code(unknown.to_code()).is_err());
+        assert!("unknown".parse::<Character>().is_err());
+        assert!(!Character::all().contains(&unknown));
+    }
+
+    #[test]
+    fn character_code_round_trip() {
+        for &c in Character::all() {
+            assert_eq!(Character::from_code(c.to_code()).unwrap(), c);
+            assert_eq!(Character::try_from(c.to_code()).unwrap(), c);
+        }
+        let zato: Result<Character> = "ZAT".try_into();
+        assert_eq!(zato.unwrap(), Character::Zato);
+        assert!(matches!(
+            Character::try_from("zat"),
+            Err(Error::InvalidCharacterName(code)) if code == "zat"
+        ));
+    }
+
+    #[test]
+    fn character_names() {
+        assert_eq!(Character::Sol.to_string(), "Sol Badguy");
+        assert_eq!(
+            Character::Ky.localized_name(Language::English),
+            Character::Ky.to_string()
+        );
+        assert_eq!(
+            Character::Sol.localized_name(Language::Japanese),
+            "ソル＝バッドガイ"
+        );
+        assert_eq!(Character::Nagoriyuki.name_ja(), "名残雪");
+    }
+
+    #[test]
+    fn icon_slugs() {
+        assert_eq!(Character::Sol.icon_slug(), "sol");
+        assert_eq!(Character::HappyChaos.icon_slug(), "cos");
+        let slugs = Character::all()
+            .iter()
+            .map(|c| c.icon_slug())
+            .collect::<std::collections::HashSet<_>>();
+        assert_eq!(slugs.len(), Character::all().len());
+    }
+
+    #[test]
+    fn theme_colors() {
+        assert_eq!(Character::Sol.theme_color(), (178, 34, 34));
+        let colors = Character::all()
+            .iter()
+            .map(|c| c.theme_color())
+            .collect::<std::collections::HashSet<_>>();
+        assert_eq!(colors.len(), Character::all().len());
+    }
+
+    #[test]
+    fn seasons() {
+        let base = Character::all()
+            .iter()
+            .filter(|c| c.is_base_roster())
+            .count();
+        assert_eq!(base, 15);
+        assert!(Character::all().iter().all(|c| c.season().is_some()));
+        // Characters are numbered in release order, so seasons never go down
+        assert!(Character::all()
+            .windows(2)
+            .all(|w| w[0].season() <= w[1].season()));
+        assert_eq!(Character::Unknown(0x16).season(), None);
+    }
+
+    #[test]
+    fn archetypes() {
+        let grapplers = Character::all()
+            .iter()
+            .filter(|c| c.is_grappler())
+            .collect::<Vec<_>>();
+        assert_eq!(grapplers, [&Character::Potemkin, &Character::Goldlewis]);
+        assert_eq!(Character::Chipp.archetype(), Archetype::Rushdown);
+    }
+
+    #[test]
+    fn parse_character() {
+        assert_eq!("Sol Badguy".parse::<Character>().unwrap(), Character::Sol);
+        assert_eq!("i-no".parse::<Character>().unwrap(), Character::Ino);
+        assert_eq!("COS".parse::<Character>().unwrap(), Character::HappyChaos);
+        assert_eq!("zat".parse::<Character>().unwrap(), Character::Zato);
+        assert_eq!("Pot".parse::<Character>().unwrap(), Character::Potemkin);
+        assert_eq!("Nago".parse::<Character>().unwrap(), Character::Nagoriyuki);
+        assert!(matches!(
+            "Dan".parse::<Character>(),
+            Err(Error::InvalidCharacterName(_))
+        ));
+    }
+
+    #[test]
+    fn floor_round_trip() {
+        for &f in Floor::all() {
+            assert_eq!(Floor::from_u8(f.to_u8()).unwrap(), f);
+            let hex = u8::from_str_radix(&f.as_hex(), 16).unwrap();
+            assert_eq!(Floor::from_u8(hex).unwrap(), f);
+        }
+    }
+
+    #[test]
+    fn floor_order() {
+        assert!(Floor::F1 < Floor::F2);
+        assert!(Floor::F9 < Floor::F10);
+        assert!(Floor::F10 < Floor::Celestial);
+        assert!(Floor::all().windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn floor_display() {
+        assert_eq!(Floor::F1.to_string(), "Floor 1");
+        assert_eq!(Floor::F10.to_string(), "Floor 10");
+        assert_eq!(Floor::Celestial.to_string(), "Celestial");
+        assert_eq!(Floor::all().len(), 11);
+    }
+
+    #[test]
+    fn parse_floor() {
+        assert_eq!("celestial".parse::<Floor>().unwrap(), Floor::Celestial);
+        assert_eq!("10".parse::<Floor>().unwrap(), Floor::F10);
+        assert_eq!("F7".parse::<Floor>().unwrap(), Floor::F7);
+        assert_eq!(" f1 ".parse::<Floor>().unwrap(), Floor::F1);
+        for &floor in Floor::all() {
+            assert_eq!(floor.to_string().parse::<Floor>().unwrap(), floor);
+        }
+        assert!("0".parse::<Floor>().is_err());
+        assert!("F11".parse::<Floor>().is_err());
+        assert!("heaven".parse::<Floor>().is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn match_serde_round_trip() {
+        let m = Match {
+            timestamp: Utc.with_ymd_and_hms(2022, 2, 6, 4, 7, 59).unwrap(),
+            floor: Floor::Celestial,
+            players: (
+                Player {
+                    id: 210611232517053199,
+                    character: Character::Giovanna,
+                    name: "limon".into(),
+                },
+                Player {
+                    id: 210818223745601103,
+                    character: Character::Nagoriyuki,
+                    name: "SamuraiPizzaCat".into(),
+                },
+            ),
+            winner: Winner::Player2,
+        };
+
+        let json = serde_json::to_string(&m).unwrap();
+        expect_test::expect![[r#"{"timestamp":"2022-02-06T04:07:59Z","floor":"Celestial","players":[{"id":210611232517053199,"character":"Giovanna","name":"limon"},{"id":210818223745601103,"character":"Nagoriyuki","name":"SamuraiPizzaCat"}],"winner":"Player2"}"#]].assert_eq(&json);
+
+        let de: Match = serde_json::from_str(&json).unwrap();
+        assert_eq!(de, m);
+        assert_eq!(de.players().0.name, m.players().0.name);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn timestamp_serde_rfc3339() {
+        let json = r#"{"timestamp":"2022-02-06T13:07:59+09:00","floor":"F1","players":[{"id":1,"character":"Sol","name":""},{"id":2,"character":"Ky","name":""}],"winner":"Player1"}"#;
+        let m: Match = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            m.timestamp(),
+            &Utc.with_ymd_and_hms(2022, 2, 6, 4, 7, 59).unwrap()
+        );
+        let serialized = serde_json::to_value(&m).unwrap();
+        assert_eq!(serialized["timestamp"], "2022-02-06T04:07:59Z");
+
+        let invalid = json.replace("2022-02-06T13:07:59+09:00", "1644120479");
+        assert!(serde_json::from_str::<Match>(&invalid).is_err());
+    }
+
     #[tokio::test]
     async fn query_replays() {
         use crate::*;
@@ -501,6 +1588,7 @@ mod test {
         let n_replays_per_page = 127;
         let (replays, errors) = get_replays(
             &ctx,
+            Platform::PC,
             n_pages,
             n_replays_per_page,
             QueryParameters::default()
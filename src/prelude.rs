@@ -0,0 +1,8 @@
+//! The commonly used types and request functions, imported with `use ggst_api::prelude::*`. The
+//! Result alias is left out so it does not shadow std::result::Result.
+pub use crate::error::Error;
+pub use crate::requests::{
+    get_player_replays, get_recent_matches, get_replays, get_replays_page, get_user, get_users,
+    replay_stream, Context, Platform, ReplayQuery,
+};
+pub use crate::{Character, Floor, Match, Matchup, Player, QueryParameters, Stats, User, Winner};
@@ -1,22 +1,175 @@
 use crate::{error::*, *};
 
-use chrono::{DateTime, NaiveDateTime, Utc};
+use chrono::{DateTime, FixedOffset, NaiveDateTime, Utc};
+use futures_util::future::{self, BoxFuture};
+use futures_util::stream::{self, Stream, StreamExt};
 use reqwest::{self, header};
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
 use std::str;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::time::Instant;
+
+// Emits a tracing event if the tracing feature is enabled and expands to nothing otherwise.
+macro_rules! log_event {
+    ($level:ident, $($arg:tt)*) => {
+        #[cfg(feature = "tracing")]
+        tracing::$level!($($arg)*);
+    };
+}
 
 const DEFAULT_BASE_URL: &str = "https://ggst-game.guiltygear.com";
 
-/// Context struct which contains the base urls used for api requests. Use the associated methods
-/// to overwrite urls if necessary.
+/// The number of pages the server serves for a query
+pub const MAX_PAGES: usize = 100;
+/// The largest number of replays per page the server accepts
+pub const MAX_REPLAYS_PER_PAGE: usize = 127;
+
+// Upper bounds for the delay between retries, so a large base delay or a hostile Retry-After
+// header cannot park a request for days
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(300);
+
+/// Context struct which contains the base urls and the http client used for api requests. Use
+/// the associated methods to overwrite them if necessary. The client is shared by all requests made
+/// with the same context.
+///
+/// Requests can be cancelled by dropping their future, e.g. with tokio::time::timeout or
+/// tokio::select!. This aborts the http request and leaves the context usable: nothing is cached
+/// for an unfinished request and a request waiting for the rate limit gives its slot back if no
+/// later request has reserved one yet.
 pub struct Context {
     base_url: String,
+    client: reqwest::Client,
+    timeout: Option<Duration>,
+    max_attempts: u32,
+    retry_delay: Duration,
+    rate_limit: Option<RateLimit>,
+    transport: Option<Box<dyn Transport>>,
+    headers: header::HeaderMap,
+    cache: Option<Cache>,
+    on_request: Option<Box<OnRequest>>,
+}
+
+type OnRequest = dyn Fn(&RequestMetrics) + Send + Sync;
+
+/// Measurements of a finished api request, passed to the callback set with Context::on_request()
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct RequestMetrics {
+    pub url: String,
+    /// The http status of the last attempt, None if there was no http response, e.g. after a
+    /// timeout or when the request went through a custom Transport
+    pub status: Option<u16>,
+    /// The size of the response body, 0 if the request failed
+    pub bytes: usize,
+    /// The time from the first attempt until the end of the last one, including retry delays
+    /// and waiting for the rate limit
+    pub elapsed: Duration,
+    /// The number of attempts after the first one
+    pub retries: u32,
+}
+
+// Responses by request path and data. Only responses that decode as an api response are stored.
+struct Cache {
+    ttl: Duration,
+    entries: Mutex<HashMap<String, (Instant, bytes::Bytes)>>,
+}
+
+impl Cache {
+    fn get(&self, key: &str) -> Option<bytes::Bytes> {
+        let mut entries = self.entries.lock().unwrap();
+        match entries.get(key) {
+            Some((time, bytes)) if time.elapsed() < self.ttl => Some(bytes.clone()),
+            Some(_) => {
+                entries.remove(key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    fn insert(&self, key: String, bytes: bytes::Bytes) {
+        let is_success = rmp_serde::decode::from_slice::<
+            messagepack::Response<serde_crate::de::IgnoredAny>,
+        >(&bytes)
+        .is_ok_and(|response| response.header.is_success());
+        let mut entries = self.entries.lock().unwrap();
+        // Drop expired entries of other requests as well, so varying queries do not grow the map
+        entries.retain(|_, (time, _)| time.elapsed() < self.ttl);
+        if is_success {
+            entries.insert(key, (Instant::now(), bytes));
+        }
+    }
+}
+
+/// Sends the api requests of a context instead of its http client, see Context::with_transport().
+/// Implement it to serve recorded responses in tests without a network connection. The returned
+/// `BoxFuture<'a, T>` is a `Pin<Box<dyn Future<Output = T> + Send + 'a>>`.
+pub trait Transport: Send + Sync {
+    /// Post the form field `data` with the hex encoded request to `url` and return the body of
+    /// the response
+    fn post<'a>(&'a self, url: &'a str, data: &'a str) -> BoxFuture<'a, Result<Vec<u8>>>;
+}
+
+impl<T: Transport + ?Sized> Transport for std::sync::Arc<T> {
+    fn post<'a>(&'a self, url: &'a str, data: &'a str) -> BoxFuture<'a, Result<Vec<u8>>> {
+        (**self).post(url, data)
+    }
+}
+
+// Spaces requests evenly by reserving the next free slot before a request is sent
+struct RateLimit {
+    interval: Duration,
+    next: Mutex<Instant>,
+}
+
+impl RateLimit {
+    async fn wait(&self) {
+        let slot = {
+            let mut next = self.next.lock().unwrap();
+            let slot = (*next).max(Instant::now());
+            *next = slot + self.interval;
+            slot
+        };
+        // Give the slot back if the request is dropped while waiting for it
+        let release = ReleaseSlot { limit: self, slot };
+        tokio::time::sleep_until(slot).await;
+        std::mem::forget(release);
+    }
+}
+
+struct ReleaseSlot<'a> {
+    limit: &'a RateLimit,
+    slot: Instant,
+}
+
+impl Drop for ReleaseSlot<'_> {
+    fn drop(&mut self) {
+        let mut next = self.limit.next.lock().unwrap();
+        // Later reservations keep their slots, so only the last one can be given back
+        if *next == self.slot + self.limit.interval {
+            *next = self.slot;
+        }
+    }
 }
 
 impl Default for Context {
     fn default() -> Self {
         Context {
             base_url: DEFAULT_BASE_URL.to_string(),
+            client: reqwest::Client::new(),
+            timeout: None,
+            max_attempts: 1,
+            retry_delay: Duration::from_millis(500),
+            rate_limit: None,
+            transport: None,
+            cache: None,
+            on_request: None,
+            headers: header::HeaderMap::from_iter([(
+                header::USER_AGENT,
+                header::HeaderValue::from_static("Steam"),
+            )]),
         }
     }
 }
@@ -25,7 +178,116 @@ impl Context {
     /// Overwrite the url used for api requests. The default is https://ggst-game.guiltygear.com
     /// You can modify this to a proxy in your area for faster requests
     pub fn new(base_url: String) -> Self {
-        Context { base_url }
+        Context {
+            base_url,
+            ..Default::default()
+        }
+    }
+
+    /// Send requests to `base_url` instead of the official server, e.g. a local mock server, a
+    /// caching proxy or a mirror. Same as Context::new() but chainable with the other options. A
+    /// trailing slash is removed since the api paths start with one.
+    pub fn base_url(self, base_url: impl Into<String>) -> Self {
+        let mut base_url = base_url.into();
+        while base_url.ends_with('/') {
+            base_url.pop();
+        }
+        Context { base_url, ..self }
+    }
+
+    /// Use an existing client for all requests, e.g. to share its connection pool or to configure
+    /// tls options
+    pub fn with_client(self, client: reqwest::Client) -> Self {
+        Context { client, ..self }
+    }
+
+    /// Send all requests with `transport` instead of the http client. The timeout, retries and
+    /// rate limit of the context still apply.
+    pub fn with_transport(self, transport: impl Transport + 'static) -> Self {
+        Context {
+            transport: Some(Box::new(transport)),
+            ..self
+        }
+    }
+
+    /// Send `user_agent` as the User-Agent header instead of the default "Steam"
+    pub fn user_agent(self, user_agent: header::HeaderValue) -> Self {
+        self.header(header::USER_AGENT, user_agent)
+    }
+
+    /// Send an additional header with every request, replacing an earlier value of the same
+    /// header. Headers are not passed to a custom Transport.
+    pub fn header(mut self, name: header::HeaderName, value: header::HeaderValue) -> Self {
+        self.headers.insert(name, value);
+        self
+    }
+
+    /// Keep responses for `ttl` and answer identical requests made through this context from
+    /// memory in the meantime. The undecoded response is stored, keyed by the api path and the
+    /// encoded request, so a request only hits the cache if all of its parameters are the same.
+    /// Errors, responses with an error status in their header and responses that cannot be decoded
+    /// are never cached. Expired responses are dropped whenever a new one is stored. Nothing is
+    /// cached by default.
+    pub fn cache(self, ttl: Duration) -> Self {
+        Context {
+            cache: Some(Cache {
+                ttl,
+                entries: Mutex::new(HashMap::new()),
+            }),
+            ..self
+        }
+    }
+
+    /// Remove all cached responses so the next requests go to the server again
+    pub fn clear_cache(&self) {
+        if let Some(cache) = &self.cache {
+            cache.entries.lock().unwrap().clear();
+        }
+    }
+
+    /// Call `callback` after every request to the server with its url, status, size, duration and
+    /// number of retries, e.g. to feed a metrics system. Responses answered from the cache are not
+    /// requests and do not call it. The callback runs inside the request, so it should be quick.
+    pub fn on_request(self, callback: impl Fn(&RequestMetrics) + Send + Sync + 'static) -> Self {
+        Context {
+            on_request: Some(Box::new(callback)),
+            ..self
+        }
+    }
+
+    /// Abort requests that take longer than `timeout` with Error::Timeout. By default requests
+    /// never time out.
+    pub fn timeout(self, timeout: Duration) -> Self {
+        Context {
+            timeout: Some(timeout),
+            ..self
+        }
+    }
+
+    /// Retry requests that failed because of network errors, timeouts or server errors up to
+    /// `max_attempts` times in total. The delay between attempts starts at `base_delay` and doubles
+    /// after every attempt up to one minute. A Retry-After wait sent with a 429 response is
+    /// honored up to five minutes. Responses that cannot be parsed are never retried. By default
+    /// every request is only attempted once.
+    pub fn retries(self, max_attempts: u32, base_delay: Duration) -> Self {
+        Context {
+            max_attempts: max_attempts.max(1),
+            retry_delay: base_delay,
+            ..self
+        }
+    }
+
+    /// Send at most `requests_per_second` requests, including retries. Requests made through the
+    /// same context wait for their turn, whether they are sequential or concurrent. By default
+    /// requests are not limited since the limits of the server are not known.
+    pub fn rate_limit(self, requests_per_second: u32) -> Self {
+        Context {
+            rate_limit: Some(RateLimit {
+                interval: Duration::from_secs(1) / requests_per_second.max(1),
+                next: Mutex::new(Instant::now()),
+            }),
+            ..self
+        }
     }
 }
 
@@ -58,9 +320,14 @@ fn id_from_bytes(bytes: &[u8]) -> Result<i64> {
 /// Retrieve the latest set of replays. Each page contains approximately 10 replays by default, however this is not
 /// guaranteed. Indicate the min and maximum floor you want to query.
 /// No more than 100 pages can be queried at a time and only 127 replays per page max.
-/// If no matches can be found the parsing will fail.
-/// Usually a few replays have weird timestamps from the future. It is recommended to apply a
-/// filter on the current time before using any matches, like `.filter(|m| m.timestamp() < &chrono::Utc::now())`
+/// If no matches can be found both iterators are empty, while a response that cannot be parsed
+/// shows up as a ParseError.
+/// The server dates replays in Japan Standard Time, the timestamps of the matches are converted
+/// to UTC.
+/// The server returns the newest replays first, but duplicates are removed by collecting the
+/// matches into a sorted set, so the matches are returned oldest first, ordered like Match's Ord.
+/// Use `.rev()` to get the newest first. The same holds for all functions returning matches
+/// except get_recent_matches(), and for replay_stream() within each page.
 pub async fn get_replays<A, B, C, D, E>(
     context: &Context,
     platform: Platform,
@@ -68,20 +335,480 @@ pub async fn get_replays<A, B, C, D, E>(
     replays_per_page: usize,
     request_parameters: QueryParameters<A, B, C, D, E>,
 ) -> Result<(
-    impl Iterator<Item = Match>,
+    impl DoubleEndedIterator<Item = Match>,
+    impl Iterator<Item = ParseError>,
+)> {
+    check_page_count(pages)?;
+    check_query(replays_per_page, &request_parameters)?;
+
+    // Assume at most 10 replays per page for pre allocation
+    let mut matches = BTreeSet::new();
+    let mut errors = vec![];
+    for i in 0..pages {
+        fetch_page(
+            context,
+            platform,
+            i,
+            replays_per_page,
+            &request_parameters,
+            &mut matches,
+            &mut errors,
+        )
+        .await?;
+    }
+    Ok((matches.into_iter(), errors.into_iter()))
+}
+
+/// Same as get_replays() but requests up to `concurrency` pages at the same time. Requests still
+/// go through the rate limit of the context. The results are merged and sorted like
+/// get_replays().
+pub async fn get_replays_concurrent<A, B, C, D, E>(
+    context: &Context,
+    platform: Platform,
+    pages: usize,
+    replays_per_page: usize,
+    request_parameters: QueryParameters<A, B, C, D, E>,
+    concurrency: usize,
+) -> Result<(
+    impl DoubleEndedIterator<Item = Match>,
+    impl Iterator<Item = ParseError>,
+)> {
+    check_page_count(pages)?;
+    check_query(replays_per_page, &request_parameters)?;
+
+    let request_parameters = &request_parameters;
+    let results = stream::iter(0..pages)
+        .map(|i| async move {
+            let mut matches = BTreeSet::new();
+            let mut errors = vec![];
+            fetch_page(
+                context,
+                platform,
+                i,
+                replays_per_page,
+                request_parameters,
+                &mut matches,
+                &mut errors,
+            )
+            .await
+            .map(|()| (matches, errors))
+        })
+        .buffered(concurrency.max(1))
+        .collect::<Vec<_>>()
+        .await;
+
+    let mut matches = BTreeSet::new();
+    let mut errors = vec![];
+    for result in results {
+        let (mut page_matches, page_errors) = result?;
+        matches.append(&mut page_matches);
+        errors.extend(page_errors);
+    }
+    Ok((matches.into_iter(), errors.into_iter()))
+}
+
+/// Retrieve a single page of replays. `page` is the zero based index of the page, the server only
+/// serves the first 100 pages with at most 127 replays each.
+/// A page with fewer replays than `replays_per_page` may still be followed by more pages since
+/// duplicates and unparsable replays are removed, but an empty page means the query is exhausted.
+pub async fn get_replays_page<A, B, C, D, E>(
+    context: &Context,
+    platform: Platform,
+    page: usize,
+    replays_per_page: usize,
+    request_parameters: &QueryParameters<A, B, C, D, E>,
+) -> Result<(
+    impl DoubleEndedIterator<Item = Match>,
+    impl Iterator<Item = ParseError>,
+)> {
+    check_page(page)?;
+    check_query(replays_per_page, request_parameters)?;
+
+    let mut matches = BTreeSet::new();
+    let mut errors = vec![];
+    fetch_page(
+        context,
+        platform,
+        page,
+        replays_per_page,
+        request_parameters,
+        &mut matches,
+        &mut errors,
+    )
+    .await?;
+    Ok((matches.into_iter(), errors.into_iter()))
+}
+
+/// A single page request of replays, built with chainable methods instead of the positional
+/// arguments of get_replays_page(). The filters are the ones of QueryParameters and can each
+/// only be set once. By default it requests the first page of 10 replays.
+///
+/// ```ignore
+/// let (matches, errors) = ReplayQuery::new(Platform::PC)
+///     .floor(Floor::Celestial)
+///     .character(Character::Sol)
+///     .page(2)
+///     .page_size(100)
+///     .fetch(&context)
+///     .await?;
+/// ```
+pub struct ReplayQuery<A, B, C, D, E> {
+    platform: Platform,
+    page: usize,
+    page_size: usize,
+    parameters: QueryParameters<A, B, C, D, E>,
+}
+
+impl ReplayQuery<NoChar1Set, NoChar2Set, NoWinnerSet, NoMinFloorSet, NoMaxFloorSet> {
+    pub fn new(platform: Platform) -> Self {
+        ReplayQuery {
+            platform,
+            page: 0,
+            page_size: 10,
+            parameters: QueryParameters::default(),
+        }
+    }
+}
+
+impl<A, B, C, D, E> ReplayQuery<A, B, C, D, E> {
+    /// The zero based index of the page, see get_replays_page()
+    pub fn page(self, page: usize) -> Self {
+        ReplayQuery { page, ..self }
+    }
+
+    /// The number of replays to request, at most 127
+    pub fn page_size(self, page_size: usize) -> Self {
+        ReplayQuery { page_size, ..self }
+    }
+
+    /// Send the request, same as get_replays_page() with the arguments of the query
+    pub async fn fetch(
+        &self,
+        context: &Context,
+    ) -> Result<(
+        impl DoubleEndedIterator<Item = Match>,
+        impl Iterator<Item = ParseError>,
+    )> {
+        get_replays_page(
+            context,
+            self.platform,
+            self.page,
+            self.page_size,
+            &self.parameters,
+        )
+        .await
+    }
+
+    fn parameters<A2, B2, C2, D2, E2>(
+        self,
+        f: impl FnOnce(QueryParameters<A, B, C, D, E>) -> QueryParameters<A2, B2, C2, D2, E2>,
+    ) -> ReplayQuery<A2, B2, C2, D2, E2> {
+        ReplayQuery {
+            platform: self.platform,
+            page: self.page,
+            page_size: self.page_size,
+            parameters: f(self.parameters),
+        }
+    }
+}
+
+impl<A, B, C, E> ReplayQuery<A, B, C, NoMinFloorSet, E> {
+    /// See QueryParameters::min_floor()
+    pub fn min_floor(self, floor: Floor) -> ReplayQuery<A, B, C, MinFloorSet, E> {
+        self.parameters(|p| p.min_floor(floor))
+    }
+}
+
+impl<A, B, C, D> ReplayQuery<A, B, C, D, NoMaxFloorSet> {
+    /// See QueryParameters::max_floor()
+    pub fn max_floor(self, floor: Floor) -> ReplayQuery<A, B, C, D, MaxFloorSet> {
+        self.parameters(|p| p.max_floor(floor))
+    }
+}
+
+impl<A, B, C> ReplayQuery<A, B, C, NoMinFloorSet, NoMaxFloorSet> {
+    /// See QueryParameters::floor()
+    pub fn floor(self, floor: Floor) -> ReplayQuery<A, B, C, MinFloorSet, MaxFloorSet> {
+        self.parameters(|p| p.floor(floor))
+    }
+}
+
+impl<B, C, D, E> ReplayQuery<NoChar1Set, B, C, D, E> {
+    /// Set the player 1 character
+    pub fn character(self, character: Character) -> ReplayQuery<Char1Set, B, C, D, E> {
+        self.parameters(|p| p.character(character))
+    }
+}
+
+impl<C, D, E> ReplayQuery<Char1Set, NoChar2Set, C, D, E> {
+    /// Set the player 2 character
+    pub fn character(self, character: Character) -> ReplayQuery<Char1Set, Char2Set, C, D, E> {
+        self.parameters(|p| p.character(character))
+    }
+}
+
+impl<B, D, E> ReplayQuery<Char1Set, B, NoWinnerSet, D, E> {
+    /// See QueryParameters::winner()
+    pub fn winner(self, winner: Winner) -> ReplayQuery<Char1Set, B, WinnerSet, D, E> {
+        self.parameters(|p| p.winner(winner))
+    }
+}
+
+/// Lazily page through all replays matching the query. The next page is only requested once every
+/// match of the previous page has been consumed, so nothing is fetched ahead of the consumer.
+/// Duplicates are only removed within a page. Replays that cannot be parsed are yielded as
+/// Error::InvalidReplay and the stream continues, while a failed request ends the stream. The
+/// stream stops at the first empty page or after the last page the server serves.
+pub fn replay_stream<'a, A: 'a, B: 'a, C: 'a, D: 'a, E: 'a>(
+    context: &'a Context,
+    platform: Platform,
+    replays_per_page: usize,
+    request_parameters: QueryParameters<A, B, C, D, E>,
+) -> Result<impl Stream<Item = Result<Match>> + 'a> {
+    check_query(replays_per_page, &request_parameters)?;
+
+    let state = (0, VecDeque::new(), false, request_parameters);
+    Ok(stream::unfold(
+        state,
+        move |(mut page, mut buffer, mut done, request_parameters)| async move {
+            loop {
+                if let Some(item) = buffer.pop_front() {
+                    return Some((item, (page, buffer, done, request_parameters)));
+                }
+                if done || page >= MAX_PAGES {
+                    return None;
+                }
+
+                let mut matches = BTreeSet::new();
+                let mut errors = vec![];
+                match fetch_page(
+                    context,
+                    platform,
+                    page,
+                    replays_per_page,
+                    &request_parameters,
+                    &mut matches,
+                    &mut errors,
+                )
+                .await
+                {
+                    Ok(()) => {
+                        done = matches.is_empty() && errors.is_empty();
+                        buffer.extend(matches.into_iter().map(Ok));
+                        buffer.extend(errors.into_iter().map(|e| Err(e.into())));
+                    }
+                    Err(e) => {
+                        done = true;
+                        buffer.push_back(Err(e));
+                    }
+                }
+                page += 1;
+            }
+        },
+    ))
+}
+
+/// Retrieve the first page of replays of every floor, requesting up to `concurrency` floors at
+/// the same time like get_replays_concurrent(). Each floor has its own result, so a failed
+/// request only loses the matches of its floor. Replays that cannot be parsed are skipped. The
+/// map is a BTreeMap so iterating it goes from the lowest floor to Celestial.
+pub async fn get_all_floors(
+    context: &Context,
+    platform: Platform,
+    replays_per_page: usize,
+    concurrency: usize,
+) -> Result<BTreeMap<Floor, Result<Vec<Match>>>> {
+    check_query(replays_per_page, &QueryParameters::default())?;
+
+    let results = stream::iter(Floor::all().iter().copied())
+        .map(|floor| async move {
+            let request_parameters = QueryParameters::default().floor(floor);
+            let result =
+                get_replays_page(context, platform, 0, replays_per_page, &request_parameters)
+                    .await
+                    .map(|(matches, _)| matches.collect());
+            (floor, result)
+        })
+        .buffered(concurrency.max(1))
+        .collect::<Vec<_>>()
+        .await;
+    Ok(results.into_iter().collect())
+}
+
+/// Collect the `limit` most recent matches on a floor, sorted newest first. Pages of 127 replays
+/// are requested until enough matches have been collected, the server runs out of replays or
+/// the last page is reached. Duplicates across pages are removed and replays that
+/// cannot be parsed are skipped.
+pub async fn get_recent_matches(
+    context: &Context,
+    platform: Platform,
+    floor: Floor,
+    limit: usize,
+) -> Result<Vec<Match>> {
+    let request_parameters = QueryParameters::default().floor(floor);
+    let mut matches = BTreeSet::new();
+    for page in 0..MAX_PAGES {
+        if matches.len() >= limit {
+            break;
+        }
+        let mut page_matches = BTreeSet::new();
+        let mut errors = vec![];
+        fetch_page(
+            context,
+            platform,
+            page,
+            MAX_REPLAYS_PER_PAGE,
+            &request_parameters,
+            &mut page_matches,
+            &mut errors,
+        )
+        .await?;
+        if page_matches.is_empty() && errors.is_empty() {
+            break;
+        }
+        matches.append(&mut page_matches);
+    }
+    Ok(matches.into_iter().rev().take(limit).collect())
+}
+
+/// Collect the `limit` most recent matches of the user with id `player_id`, sorted newest first.
+/// The api cannot search for other players, so pages of 127 replays matching the query are
+/// requested and filtered with Match::contains_player() until enough matches have been found, the
+/// server runs out of replays or the last page is reached. Only the latest 100 pages are
+/// served, so for players who have not played recently fewer matches or none are found. Narrow
+/// the query, e.g. to the floor or character of the player, to search further back with the same
+/// number of requests.
+pub async fn get_player_replays<A, B, C, D, E>(
+    context: &Context,
+    platform: Platform,
+    player_id: i64,
+    limit: usize,
+    request_parameters: QueryParameters<A, B, C, D, E>,
+) -> Result<Vec<Match>> {
+    check_query(MAX_REPLAYS_PER_PAGE, &request_parameters)?;
+
+    let mut matches = BTreeSet::new();
+    for page in 0..MAX_PAGES {
+        if matches.len() >= limit {
+            break;
+        }
+        let mut page_matches = BTreeSet::new();
+        let mut errors = vec![];
+        fetch_page(
+            context,
+            platform,
+            page,
+            MAX_REPLAYS_PER_PAGE,
+            &request_parameters,
+            &mut page_matches,
+            &mut errors,
+        )
+        .await?;
+        if page_matches.is_empty() && errors.is_empty() {
+            break;
+        }
+        matches.extend(
+            page_matches
+                .into_iter()
+                .filter(|m| m.contains_player(player_id)),
+        );
+    }
+    Ok(matches.into_iter().rev().take(limit).collect())
+}
+
+/// Retrieve all matches with a timestamp in `start..end`. The server returns the newest replays
+/// first, so pages of 127 replays are requested until a page reaches back past `start`, the
+/// server runs out of replays or the last page is reached.
+pub async fn get_replays_between<A, B, C, D, E>(
+    context: &Context,
+    platform: Platform,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    request_parameters: QueryParameters<A, B, C, D, E>,
+) -> Result<(
+    impl DoubleEndedIterator<Item = Match>,
     impl Iterator<Item = ParseError>,
 )> {
-    // Check for invalid inputs
-    if pages > 100 {
+    if start > end {
+        return Err(Error::InvalidArgument(format!(
+            "start {} is after end {}",
+            start, end
+        )));
+    }
+    check_query(MAX_REPLAYS_PER_PAGE, &request_parameters)?;
+
+    let mut matches = BTreeSet::new();
+    let mut errors = vec![];
+    for page in 0..MAX_PAGES {
+        let mut page_matches = BTreeSet::new();
+        let errors_before = errors.len();
+        fetch_page(
+            context,
+            platform,
+            page,
+            MAX_REPLAYS_PER_PAGE,
+            &request_parameters,
+            &mut page_matches,
+            &mut errors,
+        )
+        .await?;
+        if page_matches.is_empty() && errors.len() == errors_before {
+            break;
+        }
+        let reached_start = page_matches.first().is_some_and(|m| m.timestamp() < &start);
+        matches.extend(
+            page_matches
+                .into_iter()
+                .filter(|m| (start..end).contains(m.timestamp())),
+        );
+        if reached_start {
+            break;
+        }
+    }
+    Ok((matches.into_iter(), errors.into_iter()))
+}
+
+fn check_page_count(pages: usize) -> Result<()> {
+    if pages > MAX_PAGES {
         return Err(Error::InvalidArgument(format!(
-            "cannot query more than 100 pages, queried {}",
-            pages
+            "cannot query more than {} pages, queried {}",
+            MAX_PAGES, pages
         )));
     }
-    if replays_per_page > 127 {
+    Ok(())
+}
+
+fn check_page(page: usize) -> Result<()> {
+    if page >= MAX_PAGES {
         return Err(Error::InvalidArgument(format!(
-            "cannot query more than 127 replays per page, queried {}",
-            replays_per_page
+            "page has to be between 0 and {}, queried {}",
+            MAX_PAGES - 1,
+            page
+        )));
+    }
+    Ok(())
+}
+
+fn check_query<A, B, C, D, E>(
+    replays_per_page: usize,
+    request_parameters: &QueryParameters<A, B, C, D, E>,
+) -> Result<()> {
+    if !(1..=MAX_REPLAYS_PER_PAGE).contains(&replays_per_page) {
+        return Err(Error::InvalidArgument(format!(
+            "replays per page have to be between 1 and {}, queried {}",
+            MAX_REPLAYS_PER_PAGE, replays_per_page
+        )));
+    }
+
+    let characters = [request_parameters.char_1, request_parameters.char_2];
+    if let Some(Character::Unknown(code)) = characters
+        .into_iter()
+        .flatten()
+        .find(|c| matches!(c, Character::Unknown(_)))
+    {
+        return Err(Error::InvalidArgument(format!(
+            "cannot filter on unknown character code {:#04x}",
+            code
         )));
     }
 
@@ -91,62 +818,290 @@ pub async fn get_replays<A, B, C, D, E>(
             request_parameters.min_floor, request_parameters.max_floor
         )));
     }
+    Ok(())
+}
 
-    let client = reqwest::Client::new();
-
-    // Assume at most 10 replays per page for pre allocation
-    let mut matches = BTreeSet::new();
-    let mut errors = vec![];
-    for i in 0..pages {
-        // Construct the query string
-        let request = messagepack::ReplayRequest {
-            header: messagepack::RequestHeader {
-                player_id: "211027113123008384".into(),
-                string2: "61a5ed4f461c2".into(),
-                int1: 2,
-                version: "0.1.6".into(),
-                platform,
-            },
-            body: messagepack::RequestBody {
-                int1: 1,
-                index: i,
-                replays_per_page,
-                query: messagepack::RequestQuery::from(&request_parameters),
-            },
-        };
-        match api_request(&client, &context.base_url, request).await? {
-            Ok(response) => {
-                parse_response(&mut matches, &mut errors, response);
-            }
-            Err(err) => {
-                errors.push(err);
-            }
+async fn fetch_page<A, B, C, D, E>(
+    context: &Context,
+    platform: Platform,
+    index: usize,
+    replays_per_page: usize,
+    request_parameters: &QueryParameters<A, B, C, D, E>,
+    matches: &mut BTreeSet<Match>,
+    errors: &mut Vec<ParseError>,
+) -> Result<()> {
+    let request = replay_request(platform, index, replays_per_page, request_parameters);
+    let response = api_request(context, request);
+    #[cfg(feature = "tracing")]
+    let response = tracing::Instrument::instrument(
+        response,
+        tracing::debug_span!("replay_page", page = index),
+    );
+    match response.await? {
+        Ok(response) => {
+            #[cfg(feature = "tracing")]
+            let (matches_before, errors_before) = (matches.len(), errors.len());
+            parse_response(matches, errors, response);
+            log_event!(
+                debug,
+                page = index,
+                matches = matches.len() - matches_before,
+                errors = errors.len() - errors_before,
+                "parsed replay page"
+            );
         }
+        Err(err) => {
+            log_event!(warn, page = index, "could not decode replay page");
+            errors.push(err);
+        }
+    }
+    Ok(())
+}
+
+/// Retrieve the profile of a user. Characters the user has never played are left out of the
+/// character statistics.
+pub async fn get_user(context: &Context, platform: Platform, user_id: i64) -> Result<User> {
+    let request = messagepack::Request {
+        header: request_header(platform),
+        body: messagepack::StatisticsRequest {
+            id: user_id.to_string(),
+            statistics_type: 7,
+            int2: -1,
+            int3: -1,
+            int4: -1,
+            int5: -1,
+        },
+    };
+    let bytes = api_request_bytes(context, request).await?;
+    let response: messagepack::Response<messagepack::StatisticsResponse> = decode(&bytes)?;
+    user_from_statistics(&response.body.json)
+}
+
+/// Retrieve the undecoded response for a single page of replays, e.g. to store it and parse it
+/// later. Takes the same arguments as get_replays_page().
+pub async fn get_replays_raw<A, B, C, D, E>(
+    context: &Context,
+    platform: Platform,
+    page: usize,
+    replays_per_page: usize,
+    request_parameters: &QueryParameters<A, B, C, D, E>,
+) -> Result<Vec<u8>> {
+    check_page(page)?;
+    check_query(replays_per_page, request_parameters)?;
+
+    let request = replay_request(platform, page, replays_per_page, request_parameters);
+    Ok(api_request_bytes(context, request).await?.to_vec())
+}
+
+fn replay_request<A, B, C, D, E>(
+    platform: Platform,
+    index: usize,
+    replays_per_page: usize,
+    request_parameters: &QueryParameters<A, B, C, D, E>,
+) -> messagepack::ReplayRequest {
+    messagepack::ReplayRequest {
+        header: request_header(platform),
+        body: messagepack::RequestBody {
+            int1: 1,
+            index,
+            replays_per_page,
+            query: messagepack::RequestQuery::from(request_parameters),
+        },
+    }
+}
+
+fn request_header(platform: Platform) -> messagepack::RequestHeader {
+    messagepack::RequestHeader {
+        player_id: "211027113123008384".into(),
+        string2: "61a5ed4f461c2".into(),
+        int1: 2,
+        version: "0.1.6".into(),
+        platform,
     }
-    Ok((matches.into_iter(), errors.into_iter()))
 }
 
 async fn api_request<T, U>(
-    client: &reqwest::Client,
-    base_url: &str,
+    context: &Context,
     request: messagepack::Request<T>,
 ) -> Result<std::result::Result<messagepack::Response<U>, ParseError>>
 where
     T: messagepack::ApiRequest,
     for<'de> U: Deserialize<'de>,
 {
-    let response = client
-        .post(String::from(base_url) + T::PATH)
-        .header(header::USER_AGENT, "Steam")
+    let bytes = api_request_bytes(context, request).await?;
+    Ok(decode(&bytes).map_err(|e| ParseError::new(show_buf(&bytes), e)))
+}
+
+async fn api_request_bytes<T>(
+    context: &Context,
+    request: messagepack::Request<T>,
+) -> Result<bytes::Bytes>
+where
+    T: messagepack::ApiRequest,
+{
+    let data = request.to_hex();
+    let key = format!("{}?{}", T::PATH, data);
+    if let Some(bytes) = context.cache.as_ref().and_then(|c| c.get(&key)) {
+        log_event!(debug, size = bytes.len(), "using cached response");
+        return Ok(bytes);
+    }
+    let start = Instant::now();
+    let mut attempt = 0;
+    let result = loop {
+        match send_request(context, T::PATH, &data).await {
+            Ok(response) => break Ok(response),
+            Err(e) if attempt + 1 < context.max_attempts && e.is_retryable() => {
+                log_event!(warn, attempt, error = %e, "request failed, retrying");
+                tokio::time::sleep(retry_delay(context.retry_delay, attempt, &e)).await;
+                attempt += 1;
+            }
+            Err(e) => break Err(e),
+        }
+    };
+    if let Some(on_request) = &context.on_request {
+        let (status, bytes) = match &result {
+            Ok((status, bytes)) => (*status, bytes.len()),
+            Err(e) => (e.status(), 0),
+        };
+        on_request(&RequestMetrics {
+            url: context.base_url.clone() + T::PATH,
+            status,
+            bytes,
+            elapsed: start.elapsed(),
+            retries: attempt,
+        });
+    }
+    let (_, bytes) = result?;
+    if let Some(cache) = &context.cache {
+        cache.insert(key, bytes.clone());
+    }
+    Ok(bytes)
+}
+
+fn retry_delay(base_delay: Duration, attempt: u32, error: &Error) -> Duration {
+    let backoff = base_delay
+        .checked_mul(2u32.saturating_pow(attempt))
+        .unwrap_or(MAX_BACKOFF)
+        .min(MAX_BACKOFF);
+    // Wait at least as long as the server asked for
+    match error {
+        Error::RateLimited {
+            retry_after: Some(retry_after),
+        } => backoff.max((*retry_after).min(MAX_RETRY_AFTER)),
+        _ => backoff,
+    }
+}
+
+// Returns the http status next to the body, which is None for a custom transport
+async fn send_request(
+    context: &Context,
+    path: &str,
+    data: &str,
+) -> Result<(Option<u16>, bytes::Bytes)> {
+    if let Some(rate_limit) = &context.rate_limit {
+        rate_limit.wait().await;
+    }
+    let url = context.base_url.clone() + path;
+    log_event!(debug, url = %url, "sending request");
+    let (status, bytes) = match &context.transport {
+        Some(transport) => {
+            let bytes = match context.timeout {
+                Some(timeout) => tokio::time::timeout(timeout, transport.post(&url, data))
+                    .await
+                    .map_err(|e| Error::Timeout(Box::new(e)))??,
+                None => transport.post(&url, data).await?,
+            };
+            (None, bytes.into())
+        }
+        None => {
+            let (status, bytes) = http_post(context, url, data).await?;
+            (Some(status), bytes)
+        }
+    };
+    log_event!(debug, size = bytes.len(), "received response");
+    if is_maintenance_page(&bytes) {
+        log_event!(warn, "server answered with a maintenance page");
+        return Err(Error::ServerUnavailable);
+    }
+    Ok((status, bytes))
+}
+
+// During maintenance the server may answer with a text page instead of messagepack, even with
+// status 200. Api responses always start with a messagepack array marker, other bodies are only
+// treated as a maintenance page if they are an html document or mention maintenance.
+fn is_maintenance_page(bytes: &[u8]) -> bool {
+    if matches!(bytes.first(), Some(0x90..=0x9f | 0xdc | 0xdd)) {
+        return false;
+    }
+    let text = match str::from_utf8(bytes) {
+        Ok(text) => text,
+        Err(_) => return false,
+    };
+    let text = text.trim_start().to_ascii_lowercase();
+    text.starts_with("<!doctype html") || text.starts_with("<html") || text.contains("maintenance")
+}
+
+async fn http_post(context: &Context, url: String, data: &str) -> Result<(u16, bytes::Bytes)> {
+    let mut builder = context
+        .client
+        .post(url)
         .header(header::CACHE_CONTROL, "no-cache")
-        .form(&[("data", request.to_hex())])
-        .send()
-        .await?;
+        .headers(context.headers.clone())
+        .form(&[("data", data)]);
+    if let Some(timeout) = context.timeout {
+        builder = builder.timeout(timeout);
+    }
+    let response = builder.send().await?;
+    if response.status() == reqwest::StatusCode::SERVICE_UNAVAILABLE {
+        log_event!(warn, "server unavailable");
+        return Err(Error::ServerUnavailable);
+    }
+    if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+        let retry_after = response
+            .headers()
+            .get(header::RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(parse_retry_after);
+        log_event!(warn, ?retry_after, "rate limited");
+        return Err(Error::RateLimited { retry_after });
+    }
+    if response.status().is_server_error() {
+        log_event!(warn, status = %response.status(), "server error");
+        response.error_for_status_ref()?;
+    }
 
     // Convert the response to raw bytes
-    let bytes = response.bytes().await?;
-    Ok(rmp_serde::decode::from_slice(&bytes)
-        .map_err(|e| ParseError::new(show_buf(&bytes), e.into())))
+    let status = response.status().as_u16();
+    Ok((status, response.bytes().await?))
+}
+
+// Retry-After is either a number of seconds or an http date
+fn parse_retry_after(value: &str) -> Option<Duration> {
+    if let Ok(seconds) = value.trim().parse() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let date = DateTime::parse_from_rfc2822(value).ok()?;
+    Some(
+        (date.with_timezone(&Utc) - Utc::now())
+            .to_std()
+            .unwrap_or_default(),
+    )
+}
+
+/// Parse a response of the replay api without making a request, e.g. one saved with
+/// get_replays_raw(). Duplicates are removed like in get_replays(). Fails if the data is not a
+/// replay response at all.
+pub fn parse_matches(
+    data: &[u8],
+) -> Result<(
+    impl DoubleEndedIterator<Item = Match>,
+    impl Iterator<Item = ParseError>,
+)> {
+    let response = decode(data).map_err(|e| ParseError::new(show_buf(data), e))?;
+    let mut matches = BTreeSet::new();
+    let mut errors = vec![];
+    parse_response(&mut matches, &mut errors, response);
+    Ok((matches.into_iter(), errors.into_iter()))
 }
 
 fn parse_response(
@@ -154,25 +1109,53 @@ fn parse_response(
     errors: &mut Vec<ParseError>,
     response: messagepack::ReplayResponse,
 ) {
-    for replay in response.body.replays {
-        match match_from_replay(replay.clone()) {
+    for result in replay_results(response) {
+        match result {
             Ok(m) => {
                 matches.insert(m);
             }
-            Err(e) => {
-                errors.push(ParseError::new(format!("{:#?}", replay), e));
-            }
+            Err(e) => errors.push(e),
         }
     }
 }
 
+/// Parse a response of the replay api like parse_matches(), but keep one result per replay in
+/// the order of the response, newest first. Duplicates are kept, and a failed replay is
+/// Error::InvalidReplay with its position in the page, see ParseError::index(). This helps to
+/// find the replay behind an error in a saved response.
+pub fn parse_replays(data: &[u8]) -> Result<Vec<Result<Match>>> {
+    let response = decode(data).map_err(|e| ParseError::new(show_buf(data), e))?;
+    Ok(replay_results(response)
+        .map(|result| result.map_err(Error::from))
+        .collect())
+}
+
+fn replay_results(
+    response: messagepack::ReplayResponse,
+) -> impl Iterator<Item = std::result::Result<Match, ParseError>> {
+    response
+        .body
+        .replays
+        .into_iter()
+        .enumerate()
+        .map(|(index, replay)| {
+            match_from_replay(replay.clone())
+                .map_err(|e| ParseError::new(format!("{:#?}", replay), e).with_index(index))
+        })
+}
+
+// Codes without a Character variant keep the replay with Character::Unknown holding the code
+fn character(code: u8) -> Character {
+    Character::from_u8(code).unwrap_or(Character::Unknown(code))
+}
+
 fn match_from_replay(replay: messagepack::Replay) -> Result<Match> {
     Ok(Match {
         floor: replay.floor,
         timestamp: replay.date,
         players: (
-            Player::try_from((replay.player1_character, replay.player1))?,
-            Player::try_from((replay.player2_character, replay.player2))?,
+            Player::try_from((character(replay.player1_character), replay.player1))?,
+            Player::try_from((character(replay.player2_character), replay.player2))?,
         ),
         winner: match replay.winner {
             1 => Winner::Player1,
@@ -182,6 +1165,65 @@ fn match_from_replay(replay: messagepack::Replay) -> Result<Match> {
     })
 }
 
+/// Retrieve the profiles of several users at the same time. The results are in the order of
+/// `user_ids`, so a failed request only affects the result of its user.
+pub async fn get_users(
+    context: &Context,
+    platform: Platform,
+    user_ids: &[i64],
+) -> Vec<Result<User>> {
+    future::join_all(
+        user_ids
+            .iter()
+            .map(|&user_id| get_user(context, platform, user_id)),
+    )
+    .await
+}
+
+fn user_from_statistics(json: &serde_json::Value) -> Result<User> {
+    let number = |key: &str| json.get(key).and_then(|v| v.as_u64());
+    let string = |key: &str| {
+        json.get(key)
+            .and_then(|v| v.as_str())
+            .map(String::from)
+            .ok_or(Error::UnexpectedResponse(
+                "profile is missing a string field",
+            ))
+    };
+
+    let char_stats = Character::all()
+        .iter()
+        .filter_map(|&c| {
+            let code = c.to_code();
+            // Characters that were never played have no experience
+            if number(&format!("{}_Exp", code))? == 0 {
+                return None;
+            }
+            Some((
+                c,
+                Stats {
+                    level: number(&format!("{}_Lv", code))?,
+                    win_streak: number(&format!("{}_WinChainNow", code))?,
+                    max_win_streak: number(&format!("{}_WinChainMax", code))?,
+                },
+            ))
+        })
+        .collect();
+
+    Ok(User {
+        id: json
+            .get("UserID")
+            .and_then(|v| v.as_i64())
+            .ok_or(Error::UnexpectedResponse("profile is missing the user id"))?,
+        name: string("NickName")?,
+        comment: string("PublicComment")?,
+        total_ranked_matches: number("TotalRankMatch").ok_or(Error::UnexpectedResponse(
+            "profile is missing the number of ranked matches",
+        ))?,
+        char_stats,
+    })
+}
+
 impl TryFrom<(Character, messagepack::Player)> for Player {
     type Error = Error;
     fn try_from((character, player): (Character, messagepack::Player)) -> Result<Self> {
@@ -200,6 +1242,8 @@ fn from_hex(hex: &str) -> Vec<u8> {
         .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
         .collect::<Vec<_>>()
 }
+// The messagepack types mirror the wire format, so not every field or type is used by the crate
+#[allow(dead_code)]
 mod messagepack {
     use super::*;
 
@@ -379,6 +1423,7 @@ mod messagepack {
     #[serde(crate = "serde_crate")]
     pub struct ResponseHeader {
         pub id: String,
+        // 0 in every successful response that has been recorded, presumably the result code
         pub int1: UnknownInteger,
         pub date: String,
         pub version1: String,
@@ -388,6 +1433,12 @@ mod messagepack {
         pub string2: String,
     }
 
+    impl ResponseHeader {
+        pub fn is_success(&self) -> bool {
+            self.int1 == 0
+        }
+    }
+
     #[derive(Debug, Clone, Deserialize)]
     #[serde(crate = "serde_crate")]
     pub struct ResponseBody {
@@ -404,8 +1455,9 @@ mod messagepack {
         pub int2: UnknownInteger,
         #[serde(with = "floor")]
         pub floor: Floor,
-        pub player1_character: Character,
-        pub player2_character: Character,
+        // Kept as raw bytes so an unknown character only fails its own replay instead of the page
+        pub player1_character: u8,
+        pub player2_character: u8,
         pub player1: Player,
         pub player2: Player,
         pub winner: u8,
@@ -522,7 +1574,7 @@ mod messagepack {
             D: Deserializer<'de>,
         {
             let b = String::deserialize(deserializer)?;
-            Ok(serde_json::from_str(&b).map_err(D::Error::custom)?)
+            serde_json::from_str(&b).map_err(D::Error::custom)
         }
 
         pub(crate) fn serialize<S>(
@@ -536,6 +1588,9 @@ mod messagepack {
         }
     }
 
+    // The date has no time zone and is in Japan Standard Time (UTC+9), unlike the date of the
+    // response header which is UTC: every replay of a recorded page is dated a few hours after
+    // the response when read as UTC, but before it when read as JST.
     fn deserialize_date_time<'de, D>(
         deserializer: D,
     ) -> std::result::Result<chrono::DateTime<chrono::Utc>, D::Error>
@@ -543,10 +1598,14 @@ mod messagepack {
         D: Deserializer<'de>,
     {
         let time = String::deserialize(deserializer)?;
-        Ok(DateTime::<Utc>::from_utc(
-            NaiveDateTime::parse_from_str(&time, "%Y-%m-%d %H:%M:%S").map_err(D::Error::custom)?,
-            Utc,
-        ))
+        let naive =
+            NaiveDateTime::parse_from_str(&time, "%Y-%m-%d %H:%M:%S").map_err(D::Error::custom)?;
+        let jst = FixedOffset::east_opt(9 * 3600).unwrap();
+        naive
+            .and_local_timezone(jst)
+            .single()
+            .map(|date| date.with_timezone(&Utc))
+            .ok_or_else(|| D::Error::custom("invalid replay date"))
     }
 
     mod floor {
@@ -557,7 +1616,7 @@ mod messagepack {
             D: Deserializer<'de>,
         {
             let b = u8::deserialize(deserializer)?;
-            Ok(Floor::from_u8(b).map_err(D::Error::custom)?)
+            Floor::from_u8(b).map_err(D::Error::custom)
         }
 
         pub(crate) fn serialize<S>(
@@ -595,13 +1654,33 @@ mod messagepack {
         where
             S: Serializer,
         {
-            value
-                .map_or_else(|| -1, |c| c.to_u8() as i8)
-                .serialize(serializer)
+            match value {
+                None => (-1i8).serialize(serializer),
+                // -1 means no filter and codes past 0x7f do not fit, so only known codes are sent
+                Some(Character::Unknown(code)) => Err(serde_crate::ser::Error::custom(format!(
+                    "cannot filter on unknown character code {:#04x}",
+                    code
+                ))),
+                Some(c) => (c.to_u8() as i8).serialize(serializer),
+            }
         }
     }
 }
 
+// Decode messagepack like rmp_serde::decode::from_slice() but report how far decoding got on errors
+fn decode<T>(bytes: &[u8]) -> Result<T>
+where
+    for<'de> T: Deserialize<'de>,
+{
+    let mut rest = bytes;
+    let mut deserializer = rmp_serde::Deserializer::new(&mut rest);
+    T::deserialize(&mut deserializer).map_err(|reason| Error::Parse {
+        offset: bytes.len() - rest.len(),
+        length: bytes.len(),
+        reason,
+    })
+}
+
 // Helper function for constructing error messages to avoid issues with the borrow checker
 fn show_buf<B: AsRef<[u8]>>(buf: B) -> String {
     use std::ascii::escape_default;
@@ -614,9 +1693,11 @@ fn show_buf<B: AsRef<[u8]>>(buf: B) -> String {
     .unwrap()
 }
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use super::messagepack::*;
     use super::*;
+    use chrono::TimeZone;
+    use std::sync::Arc;
 
     fn parse_response_from_bytes(
         matches: &mut BTreeSet<Match>,
@@ -649,7 +1730,7 @@ mod tests {
         const RESPONSE: &[u8] = b"\x92\x98\xad61ff0796545a9\0\xb32022/02/05 23:26:14\xa50.1.0\xa50.0.2\xa50.0.2\xa0\xa0\x94\0\0\x1e\xdc\0\x1e\x9d\xcf\x03\x0eS}\x9f\x8ds\xbf\t\x08\x0c\x0b\x95\xb2210611232517053199\xa5limon\xb176561198082398187\xaf1100001074797eb\x06\x95\xb2210818223745601103\xafSamuraiPizzaCat\xb176561199149925226\xaf110000146e8c36a\x07\x02\xb32022-02-06 04:07:59\x01\0\0\0\x9d\xcf\x03\x0eS|v\xbc6N\t\x08\x11\x0c\x95\xb2210905181006143473\xa8Haratura\xb176561198148293594\xaf11000010b3513da\x07\x95\xb2210611232517053199\xa5limon\xb176561198082398187\xaf1100001074797eb\x06\x01\xb32022-02-06 03:58:19\x01\0\0\0\x9d\xcf\x03\x0eS|lr}\xc1\t\x08\x11\x0c\x95\xb2210905181006143473\xa8Haratura\xb176561198148293594\xaf11000010b3513da\x07\x95\xb2210611232517053199\xa5limon\xb176561198082398187\xaf1100001074797eb\x06\x01\xb32022-02-06 03:56:46\x01\0\0\0\x9d\xcf\x03\x0eS|du\xac>\t\x08\x11\x0c\x95\xb2210905181006143473\xa8Haratura\xb176561198148293594\xaf11000010b3513da\x07\x95\xb2210611232517053199\xa5limon\xb176561198082398187\xaf1100001074797eb\x06\x01\xb32022-02-06 03:55:12\x01\0\0\0\x9d\xcf\x03\x0eSy?\x93\x83\x86\t\x06\x04\0\x95\xb2210825010040078270\xacKenoMcsteamo\xb176561198354688358\xaf110000117826966\x05\x95\xb2211128031436376804\xa9BundleBox\xb176561198103224698\xaf11000010885617a\x05\x01\xb32022-02-06 03:29:31\x01\0\0\0\x9d\xcf\x03\x0eSy/\xfbL\xaa\t\x06\x04\0\x95\xb2210825010040078270\xacKenoMcsteamo\xb176561198354688358\xaf110000117826966\x05\x95\xb2211128031436376804\xa9BundleBox\xb176561198103224698\xaf11000010885617a\x05\x01\xb32022-02-06 03:27:10\x01\0\0\0\x9d\xcf\x03\x0eSy\"\xfc\x1d\x85\t\x06\x04\0\x95\xb2210825010040078270\xacKenoMcsteamo\xb176561198354688358\xaf110000117826966\x05\x95\xb2211128031436376804\xa9BundleBox\xb176561198103224698\xaf11000010885617a\x05\x02\xb32022-02-06 03:24:52\x01\0\0\0\x9d\xcf\x03\x0eSx\xf9\x8c\xd2\r\t\x06\x04\x12\x95\xb2210825010040078270\xacKenoMcsteamo\xb176561198354688358\xaf110000117826966\x05\x95\xb2210719021019879063\xa9Sebastard\xb176561198354593280\xaf11000011780f600\x05\x01\xb32022-02-06 03:17:56\x01\0\0\0\x9d\xcf\x03\x0eSx\xedf\x1f\xf4\t\x06\x04\x12\x95\xb2210825010040078270\xacKenoMcsteamo\xb176561198354688358\xaf110000117826966\x05\x95\xb2210719021019879063\xa9Sebastard\xb176561198354593280\xaf11000011780f600\x05\x01\xb32022-02-06 03:15:53\x01\0\0\0\x9d\xcf\x03\x0eS{q&\x8d\x92\t\x07\x05\x0c\x95\xb2220117205818084945\xa8Bugabalu\xb176561198136737187\xaf11000010a84bda3\x05\x95\xb2210611232517053199\xa5limon\xb176561198082398187\xaf1100001074797eb\x06\x02\xb32022-02-06 03:14:30\x01\0\0\0\x9d\xcf\x03\x0eSx\xe0+\xf8\xf7\t\x06\x04\x12\x95\xb2210825010040078270\xacKenoMcsteamo\xb176561198354688358\xaf110000117826966\x05\x95\xb2210719021019879063\xa9Sebastard\xb176561198354593280\xaf11000011780f600\x05\x02\xb32022-02-06 03:13:31\x01\0\0\0\x9d\xcf\x03\x0eS{c\xba\xc9z\t\x07\x05\x0c\x95\xb2220117205818084945\xa8Bugabalu\xb176561198136737187\xaf11000010a84bda3\x05\x95\xb2210611232517053199\xa5limon\xb176561198082398187\xaf1100001074797eb\x06\x01\xb32022-02-06 03:12:05\x01\0\0\0\x9d\xcf\x03\x0eS{T\xd4\\\x90\t\x07\x05\x0c\x95\xb2220117205818084945\xa8Bugabalu\xb176561198136737187\xaf11000010a84bda3\x05\x95\xb2210611232517053199\xa5limon\xb176561198082398187\xaf1100001074797eb\x06\x02\xb32022-02-06 03:09:55\x01\0\0\0\x9d\xcf\x03\x0eS{Ab\xacm\t\x07\x0c\t\x95\xb2210611232517053199\xa5limon\xb176561198082398187\xaf1100001074797eb\x06\x95\xb2210811193631829778\xaeF4ulty_R4ilgun\xb176561198351152593\xaf1100001174c75d1\x06\x02\xb32022-02-06 03:06:29\x01\0\0\0\x9d\xcf\x03\x0eS{3\xde\xb6\xa2\t\x07\x0c\t\x95\xb2210611232517053199\xa5limon\xb176561198082398187\xaf1100001074797eb\x06\x95\xb2210811193631829778\xaeF4ulty_R4ilgun\xb176561198351152593\xaf1100001174c75d1\x06\x01\xb32022-02-06 03:04:02\x01\0\0\0\x9d\xcf\x03\x0eS{)\x03G\xe2\t\x07\x0c\t\x95\xb2210611232517053199\xa5limon\xb176561198082398187\xaf1100001074797eb\x06\x95\xb2210811193631829778\xaeF4ulty_R4ilgun\xb176561198351152593\xaf1100001174c75d1\x06\x02\xb32022-02-06 03:02:20\x01\0\0\0\x9d\xcf\x03\x0eS}\xfct\x97\x16\t\x08\0\x12\x95\xb2210615035914519825\xa5BL4DE\xb176561199083465035\xaf110000142f2a94b\x07\x95\xb2210612062056984376\xb0TwitchTV/VRDante\xb176561198067414364\xaf11000010662f55c\x07\x01\xb32022-02-06 02:24:18\x01\0\0\0\x9d\xcf\x03\x0eS}\xf3\xeb\x0c\x8a\t\x08\0\x12\x95\xb2210615035914519825\xa5BL4DE\xb176561199083465035\xaf110000142f2a94b\x07\x95\xb2210612062056984376\xb0TwitchTV/VRDante\xb176561198067414364\xaf11000010662f55c\x07\x02\xb32022-02-06 02:22:34\x01\0\0\0\x9d\xcf\x03\x0eS}\xdb{XM\tc\0\x0e\x95\xb2210611113829735658\xa3Eli\xb176561198449379262\xaf11000011d2747be\t\x95\xb2210612045332227791\xa8R34 I-NO\xb176561198046971684\xaf1100001052b0724\t\x02\xb32022-02-06 02:22:08\x01\0\0\0\x9d\xcf\x03\x0eSy?\xd2\x135\tc\0\x07\x95\xb2210611092701986372\xa3tms\xb176561198223056552\xaf11000010fa9dea8\t\x95\xb2210611184101935607\xb0Shaco Arrombardo\xb176561198019472843\xaf110000103876dcb\t\x02\xb32022-02-06 02:19:53\x01\0\0\0\x9d\xcf\x03\x0eS}\xca\xaeev\tc\0\x0e\x95\xb2210611113829735658\xa3Eli\xb176561198449379262\xaf11000011d2747be\t\x95\xb2210612045332227791\xa8R34 I-NO\xb176561198046971684\xaf1100001052b0724\t\x02\xb32022-02-06 02:19:26\x01\0\0\0\x9d\xcf\x03\x0eSy0\x12\xfd\x84\tc\0\x07\x95\xb2210611092701986372\xa3tms\xb176561198223056552\xaf11000010fa9dea8\t\x95\xb2210611184101935607\xb0Shaco Arrombardo\xb176561198019472843\xaf110000103876dcb\t\x01\xb32022-02-06 02:17:29\x01\0\0\0\x9d\xcf\x03\x0eSy$#\xb0\xfc\tc\0\x07\x95\xb2210611092701986372\xa3tms\xb176561198223056552\xaf11000010fa9dea8\t\x95\xb2210611184101935607\xb0Shaco Arrombardo\xb176561198019472843\xaf110000103876dcb\t\x01\xb32022-02-06 02:15:28\x01\0\0\0\x9d\xcf\x03\x0eS}\xc5\x15\xcf\xf1\t\x08\x12\x12\x95\xb2210612062056984376\xb0TwitchTV/VRDante\xb176561198067414364\xaf11000010662f55c\x07\x95\xb2210611172901281375\xa4g5h3\xb176561198066767737\xaf110000106591779\x07\x02\xb32022-02-06 02:14:49\x01\0\0\0\x9d\xcf\x03\x0eS}\xb9w\xc3_\t\x08\x12\x12\x95\xb2210612062056984376\xb0TwitchTV/VRDante\xb176561198067414364\xaf11000010662f55c\x07\x95\xb2210611172901281375\xa4g5h3\xb176561198066767737\xaf110000106591779\x07\x01\xb32022-02-06 02:12:53\x01\0\0\0\x9d\xcf\x03\x0eS}\x95\x1a\x14\xd0\tc\r\0\x95\xb2210611163406897038\xabKidSusSauce\xb176561198796113273\xaf110000131d20579\t\x95\xb2210611113829735658\xa3Eli\xb176561198449379262\xaf11000011d2747be\t\x01\xb32022-02-06 02:10:27\x01\0\0\0\x9d\xcf\x03\x0eS}\xa7$\x04\x91\t\x08\x12\x12\x95\xb2210612062056984376\xb0TwitchTV/VRDante\xb176561198067414364\xaf11000010662f55c\x07\x95\xb2210611172901281375\xa4g5h3\xb176561198066767737\xaf110000106591779\x07\x01\xb32022-02-06 02:09:46\x01\0\0\0\x9d\xcf\x03\x0eS|x.;\xd4\tc\x01\0\x95\xb2210612195532158554\xa7Nowhere\xb176561198108655731\xaf110000108d84073\t\x95\xb2210611113829735658\xa3Eli\xb176561198449379262\xaf11000011d2747be\t\x02\xb32022-02-06 02:02:47\x01\0\0\0\x9d\xcf\x03\x0eS}re;\xfc\t\x08\x12\x07\x95\xb2210612062056984376\xb0TwitchTV/VRDante\xb176561198067414364\xaf11000010662f55c\x07\x95\xb2211222194227494329\xacEpicKittyCat\xb176561198040006360\xaf110000104c0bed8\x07\x01\xb32022-02-06 02:01:01\x01\0\0\0\x9d\xcf\x03\x0eS|d\xdd\x9d\x8c\t\x08\x02\x12\x95\xb2211224234141126253\xa6Fakuto\xb176561198387121965\xaf110000119714f2d\x07\x95\xb2210612062056984376\xb0TwitchTV/VRDante\xb176561198067414364\xaf11000010662f55c\x07\x02\xb32022-02-06 01:55:39\x01\0\0\0";
         let mut matches = BTreeSet::new();
         let mut errors = Vec::new();
-        parse_response_from_bytes(&mut matches, &mut errors, &RESPONSE);
+        parse_response_from_bytes(&mut matches, &mut errors, RESPONSE);
 
         assert!(errors.is_empty(), "Got errors: {:#?}", errors);
 
@@ -659,23 +1740,161 @@ mod tests {
     #[test]
     fn test_parse_response_2() {
         // This test used to miss one replay before true messagepack parsing
-        const RESPONSE: &[u8] = b"\x92\x98\xad61ff0f60da094\0\xb32022/02/05 23:59:28\xa50.1.0\xa50.0.2\xa50.0.2\xa0\xa0\x94\0\0\n\x9a\x9d\xcf\x03\x0eS}\x9f\x8ds\xbf\t\x08\x0c\x0b\x95\xb2210611232517053199\xa5limon\xb176561198082398187\xaf1100001074797eb\x06\x95\xb2210818223745601103\xafSamuraiPizzaCat\xb176561199149925226\xaf110000146e8c36a\x07\x02\xb32022-02-06 04:07:59\x01\0\0\0\x9d\xcf\x03\x0eS|v\xbc6N\t\x08\x11\x0c\x95\xb2210905181006143473\xa8Haratura\xb176561198148293594\xaf11000010b3513da\x07\x95\xb2210611232517053199\xa5limon\xb176561198082398187\xaf1100001074797eb\x06\x01\xb32022-02-06 03:58:19\x01\0\0\0\x9d\xcf\x03\x0eS|lr}\xc1\t\x08\x11\x0c\x95\xb2210905181006143473\xa8Haratura\xb176561198148293594\xaf11000010b3513da\x07\x95\xb2210611232517053199\xa5limon\xb176561198082398187\xaf1100001074797eb\x06\x01\xb32022-02-06 03:56:46\x01\0\0\0\x9d\xcf\x03\x0eS|du\xac>\t\x08\x11\x0c\x95\xb2210905181006143473\xa8Haratura\xb176561198148293594\xaf11000010b3513da\x07\x95\xb2210611232517053199\xa5limon\xb176561198082398187\xaf1100001074797eb\x06\x01\xb32022-02-06 03:55:12\x01\x01\0\0\x9d\xcf\x03\x0eSy?\x93\x83\x86\t\x06\x04\0\x95\xb2210825010040078270\xacKenoMcsteamo\xb176561198354688358\xaf110000117826966\x05\x95\xb2211128031436376804\xa9BundleBox\xb176561198103224698\xaf11000010885617a\x05\x01\xb32022-02-06 03:29:31\x01\0\0\0\x9d\xcf\x03\x0eSy/\xfbL\xaa\t\x06\x04\0\x95\xb2210825010040078270\xacKenoMcsteamo\xb176561198354688358\xaf110000117826966\x05\x95\xb2211128031436376804\xa9BundleBox\xb176561198103224698\xaf11000010885617a\x05\x01\xb32022-02-06 03:27:10\x01\0\0\0\x9d\xcf\x03\x0eSy\"\xfc\x1d\x85\t\x06\x04\0\x95\xb2210825010040078270\xacKenoMcsteamo\xb176561198354688358\xaf110000117826966\x05\x95\xb2211128031436376804\xa9BundleBox\xb176561198103224698\xaf11000010885617a\x05\x02\xb32022-02-06 03:24:52\x01\0\0\0\x9d\xcf\x03\x0eSx\xf9\x8c\xd2\r\t\x06\x04\x12\x95\xb2210825010040078270\xacKenoMcsteamo\xb176561198354688358\xaf110000117826966\x05\x95\xb2210719021019879063\xa9Sebastard\xb176561198354593280\xaf11000011780f600\x05\x01\xb32022-02-06 03:17:56\x01\0\0\0\x9d\xcf\x03\x0eSx\xedf\x1f\xf4\t\x06\x04\x12\x95\xb2210825010040078270\xacKenoMcsteamo\xb176561198354688358\xaf110000117826966\x05\x95\xb2210719021019879063\xa9Sebastard\xb176561198354593280\xaf11000011780f600\x05\x01\xb32022-02-06 03:15:53\x01\0\0\0\x9d\xcf\x03\x0eS{q&\x8d\x92\t\x07\x05\x0c\x95\xb2220117205818084945\xa8Bugabalu\xb176561198136737187\xaf11000010a84bda3\x05\x95\xb2210611232517053199\xa5limon\xb176561198082398187\xaf1100001074797eb\x06\x02\xb32022-02-06 03:14:30\x01\0\0\0";
-
         let mut matches = BTreeSet::new();
         let mut errors = Vec::new();
-        parse_response_from_bytes(&mut matches, &mut errors, &RESPONSE);
+        parse_response_from_bytes(&mut matches, &mut errors, RESPONSE_2);
 
         assert!(errors.is_empty(), "Got errors: {:#?}", errors);
 
         expect_test::expect_file!["../test_data/replay_response_2.txt"].assert_debug_eq(&matches);
     }
 
+    #[test]
+    fn offline_parsing() {
+        let (matches, errors) = parse_matches(RESPONSE_2).unwrap();
+        assert_eq!(matches.count(), 10);
+        assert_eq!(errors.count(), 0);
+
+        let (matches, _) = parse_matches(EMPTY_RESPONSE).unwrap();
+        assert_eq!(matches.count(), 0);
+
+        assert!(matches!(
+            parse_matches(b"not messagepack"),
+            Err(Error::InvalidReplay(_))
+        ));
+    }
+
+    #[test]
+    fn truncated_response() {
+        let length = RESPONSE_2.len() - 20;
+        let error = decode::<messagepack::ReplayResponse>(&RESPONSE_2[..length]).unwrap_err();
+        assert!(matches!(error, Error::Parse { offset, .. } if offset == length));
+        expect_test::expect![[r#"
+            Invalid messagepack at byte 1729 of 1729: IO error while reading data: unexpected end of file
+        "#]]
+        .assert_eq(&format!("{}\n", error));
+    }
+
+    #[test]
+    fn invalid_marker_offset() {
+        // Replace the marker of the first replay with 0xc1, which is never used in messagepack
+        let mut response = RESPONSE_2.to_vec();
+        let pos = response.windows(2).position(|w| w == b"\x9d\xcf").unwrap();
+        response[pos] = 0xc1;
+        let error = decode::<messagepack::ReplayResponse>(&response).unwrap_err();
+        assert!(
+            matches!(error, Error::Parse { offset, .. } if offset == pos + 1),
+            "{}",
+            error
+        );
+    }
+
+    #[test]
+    fn utc_timestamps() {
+        let response: messagepack::ReplayResponse =
+            rmp_serde::decode::from_slice(RESPONSE_2).unwrap();
+        // The header id starts with the unix timestamp of the response date in hex
+        let id_time = i64::from_str_radix(&response.header.id[..8], 16).unwrap();
+        assert_eq!(response.header.date, "2022/02/05 23:59:28");
+        assert_eq!(
+            DateTime::from_timestamp(id_time, 0).unwrap().to_string(),
+            "2022-02-05 23:59:28 UTC"
+        );
+
+        // The replay dates are JST, so the newest replay is from before the response
+        let m = match_from_replay(response.body.replays[0].clone()).unwrap();
+        assert_eq!(m.timestamp().to_rfc3339(), "2022-02-05T19:07:59+00:00");
+        assert!(response
+            .body
+            .replays
+            .iter()
+            .all(|r| r.date <= DateTime::from_timestamp(id_time, 0).unwrap()));
+    }
+
+    #[test]
+    fn player_sides() {
+        let response: messagepack::ReplayResponse =
+            rmp_serde::decode::from_slice(RESPONSE_2).unwrap();
+        for replay in response.body.replays {
+            let m = match_from_replay(replay.clone()).unwrap();
+            assert_eq!(m.player1().id().to_string(), replay.player1.id);
+            assert_eq!(u8::from(m.player1().character()), replay.player1_character);
+            assert_eq!(m.player2().id().to_string(), replay.player2.id);
+            assert_eq!(u8::from(m.player2().character()), replay.player2_character);
+            let winner = match m.winner_side() {
+                Winner::Player1 => replay.player1.id,
+                Winner::Player2 => replay.player2.id,
+            };
+            assert_eq!(m.winner().id().to_string(), winner);
+        }
+    }
+
+    #[test]
+    fn unknown_character() {
+        // Replace the character of player 1 in the first replay with an unknown code
+        let mut response = RESPONSE_2.to_vec();
+        let pos = response
+            .windows(5)
+            .position(|w| w == b"\t\x08\x0c\x0b\x95")
+            .unwrap();
+        response[pos + 2] = 0x7f;
+
+        let mut matches = BTreeSet::new();
+        let mut errors = Vec::new();
+        parse_response_from_bytes(&mut matches, &mut errors, &response);
+
+        assert_eq!(errors.len(), 0);
+        assert_eq!(matches.len(), 10);
+        let unknown = matches
+            .iter()
+            .filter(|m| m.involves_character(Character::Unknown(0x7f)))
+            .collect::<Vec<_>>();
+        assert_eq!(unknown.len(), 1);
+        assert_eq!(unknown[0].player1().name(), "limon");
+    }
+
+    #[test]
+    fn replay_error_index() {
+        // Set the winner of the fourth replay to an invalid value, the response is
+        // [header, [int1, int2, int3, replays]] and the winner is the eighth field of a replay
+        let mut response = rmpv::decode::read_value(&mut &RESPONSE_2[..]).unwrap();
+        let mut field = &mut response;
+        for index in [1, 3, 3, 7] {
+            field = match field {
+                rmpv::Value::Array(values) => &mut values[index],
+                _ => panic!("expected an array"),
+            };
+        }
+        *field = rmpv::Value::from(9);
+        let mut bytes = vec![];
+        rmpv::encode::write_value(&mut bytes, &response).unwrap();
+
+        let results = parse_replays(&bytes).unwrap();
+        assert_eq!(results.len(), 10);
+        let failed = results
+            .iter()
+            .enumerate()
+            .filter_map(|(i, r)| match r {
+                Err(Error::InvalidReplay(e)) => Some((i, e.index())),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(failed, [(3, Some(3))]);
+
+        let (_, errors) = parse_matches(&bytes).unwrap();
+        let errors = errors.collect::<Vec<_>>();
+        assert_eq!(errors[0].index(), Some(3));
+        assert!(errors[0]
+            .to_string()
+            .starts_with("Could not parse replay 3: "));
+    }
+
     #[test]
     fn test_parse_response_3() {
         // This test used to miss one replay before true messagepack parsing
         const RESPONSE: &[u8] = b"\x92\x98\xad61ffa1560e387\0\xb32022/02/06 10:22:14\xa50.1.0\xa50.0.2\xa50.0.2\xa0\xa0\x94\0\x04\n\x9a\x9d\xcf\x03\x0e\n\xb0\x95(\xcd2\x07c\x06\x07\x95\xb2210611073056107537\xa3Mar\xb176561197993198569\xaf110000101f683e9\t\x95\xb2210611121603560347\xadLuna Goodgirl\xb176561197977446342\xaf1100001010627c6\t\x01\xb32022-01-25 18:53:19\x01\x01\x01\x01\x9d\xcf\x03\r\xfb5{F6\"\x07c\x06\x07\x95\xb2210611073056107537\xa3Mar\xb176561197993198569\xaf110000101f683e9\t\x95\xb2210611162113864298\xa8Lizardos\xb176561197994492361\xaf1100001020a41c9\t\x01\xb32022-01-08 16:39:30\x01\x03\x01\x01\x9d\xcf\x02\xed\xbb\xb9\x7f\xdd?!\x06c\x06\x05\x95\xb2210611073056107537\xa3Mar\xb176561197993198569\xaf110000101f683e9\t\x95\xb2210611095248078392\xa9MOMO MODY\xb176561198156904572\xaf11000010bb8787c\t\x02\xb32021-10-31 16:29:42\x01\x03\x02\0\x9d\xcf\x02\xed\xa2D\x07\x98m\x1a\x05\n\x06\x0b\x95\xb2210611073056107537\xa3Mar\xb176561197993198569\xaf110000101f683e9\t\x95\xb2210611083023337322\xadPunishedVenom\xb176561198043848438\xaf110000104fb5ef6\t\x02\xb32021-10-03 17:06:24\x01\0\x02\0\x9d\xcf\x02\xec\xef\x05\xe7\xe6\xf1\x88\x04\n\x08\x07\x95\xb2210611073056107537\xa3Mar\xb176561197993198569\xaf110000101f683e9\t\x95\xb2210611072758921052\xaageorgekupo\xb176561198054369781\xaf1100001059be9f5\t\x02\xb32021-08-06 09:12:22\x01\0\x02\0\x9d\xcf\x02\xec\xed6\xf1\xea8\xbe\x04\n\x08\x0b\x95\xb2210611073056107537\xa3Mar\xb176561197993198569\xaf110000101f683e9\x08\x95\xb2210618173410867109\xabDominimator\xb176561197994451661\xaf11000010209a2cd\t\x02\xb32021-08-04 10:28:20\x01\0\x02\0\x9d\xcf\x02\xecG\xc9\xde*\x8e\xd6\x03\x07\x08\x01\x95\xb2210611073056107537\xa3Mar\xb176561197993198569\xaf110000101f683e9\x06\x95\xb2210614203011010057\xa5Hydro\xb176561198077327061\xaf110000106fa36d5\x06\x02\xb32021-06-22 21:49:19\x01\0\x02\0\x9d\xcf\x03\x0eSo\xd3qzH\tc\r\x06\x95\xb2210611114424649707\xa9Pistachio\xb176561198074756096\xaf110000106d2fc00\t\x95\xb2210611073056107537\xa3Mar\xb176561197993198569\xaf110000101f683e9\t\x02\xb32022-02-05 17:15:39\x01\0\0\0\x9d\xcf\x03\x0eSo\xbc\x9cz\x82\tc\x02\x06\x95\xb2210611151221285918\xa7Rikkumi\xb176561198117246557\xaf1100001095b565d\t\x95\xb2210611073056107537\xa3Mar\xb176561197993198569\xaf110000101f683e9\t\x02\xb32022-02-05 17:11:56\x01\0\0\0\x9d\xcf\x03\x0eSo\xae.\x18\xcc\tc\x02\x06\x95\xb2210611151221285918\xa7Rikkumi\xb176561198117246557\xaf1100001095b565d\t\x95\xb2210611073056107537\xa3Mar\xb176561197993198569\xaf110000101f683e9\t\x02\xb32022-02-05 17:09:14\x01\0\0\0";
 
-        let result = rmp_serde::decode::from_slice::<messagepack::ReplayResponse>(&RESPONSE);
+        let result = rmp_serde::decode::from_slice::<messagepack::ReplayResponse>(RESPONSE);
 
         expect_test::expect_file!["../test_data/replay_response_3.txt"].assert_debug_eq(&result);
     }
@@ -692,6 +1911,774 @@ mod tests {
         expect_test::expect_file!["../test_data/replay_response_4.txt"].assert_debug_eq(&result);
     }
 
+    // The statistics of type 7 for the profile of a user
+    const STATISTICS_RESPONSE: &str = "9298AD3632306133393039363765346300B3323032322F30322F31342031313A31323A3039A5302E312E30A5302E302E32A5302E302E32A0A09200DA13BA7B22414E4A5F426164676531223A323130332C22414E4A5F4261646765315F56616C223A392C22414E4A5F426164676532223A3530343030302C22414E4A5F4261646765325F56616C223A302C22414E4A5F426164676533223A3530313030302C22414E4A5F4261646765335F56616C223A312C22414E4A5F457870223A302C22414E4A5F4C76223A312C22414E4A5F4E6578744C76457870223A3130302C22414E4A5F504D5F57696E73223A302C22414E4A5F57696E436861696E4D6178223A302C22414E4A5F57696E436861696E4E6F77223A302C2241584C5F426164676531223A323130332C2241584C5F4261646765315F56616C223A392C2241584C5F426164676532223A3530343030302C2241584C5F4261646765325F56616C223A302C2241584C5F426164676533223A3530313030302C2241584C5F4261646765335F56616C223A312C2241584C5F457870223A302C2241584C5F4C76223A312C2241584C5F4E6578744C76457870223A3130302C2241584C5F504D5F57696E73223A302C2241584C5F57696E436861696E4D6178223A302C2241584C5F57696E436861696E4E6F77223A302C224163636F756E744944223A37363536313139373936303435363534322C2241766174617241757261223A302C22417661746172417572615465726D223A302C22424B4E5F426164676531223A323130332C22424B4E5F4261646765315F56616C223A392C22424B4E5F426164676532223A3530343030302C22424B4E5F4261646765325F56616C223A302C22424B4E5F426164676533223A3530313030302C22424B4E5F4261646765335F56616C223A312C22424B4E5F457870223A302C22424B4E5F4C76223A312C22424B4E5F4E6578744C76457870223A3130302C22424B4E5F504D5F57696E73223A302C22424B4E5F57696E436861696E4D6178223A302C22424B4E5F57696E436861696E4E6F77223A302C224348505F426164676531223A323130332C224348505F4261646765315F56616C223A392C224348505F426164676532223A3530343030302C224348505F4261646765325F56616C223A302C224348505F426164676533223A3530313030302C224348505F4261646765335F56616C223A312C224348505F457870223A302C224348505F4C76223A312C224348505F4E6578744C76457870223A3130302C224348505F504D5F57696E73223A302C224348505F57696E436861696E4D6178223A302C224348505F57696E436861696E4E6F77223A302C22434F535F426164676531223A3530333030392C22434F535F4261646765315F56616C223A313233382C22434F535F426164676532223A3530323138392C22434F535F4261646765325F56616C223A313534362C22434F535F426164676533223A3530313030332C22434F535F4261646765335F56616C223A313534362C22434F535F457870223A37353838373135342C22434F535F4C76223A313534362C22434F535F4E6578744C76457870223A37353932323530302C22434F535F504D5F57696E73223A302C22434F535F57696E436861696E4D6178223A3131382C22434F535F57696E436861696E4E6F77223A31302C22436F6E646974696F6E426974223A2D313032352C224461746148696464656E223A312C2244656D6F7465645F4275727374223A302C2244656D6F7465645F5243223A302C2244656D6F7465645F52434D6F7665223A302C2244656D6F7465645F5243536B696C6C223A302C2244656D6F7465645F556C74696D617465223A302C2244656D6F7465645F575342223A302C224641555F426164676531223A323130332C224641555F4261646765315F56616C223A392C224641555F426164676532223A3530343030302C224641555F4261646765325F56616C223A302C224641555F426164676533223A3530313030302C224641555F4261646765335F56616C223A312C224641555F457870223A302C224641555F4C76223A312C224641555F4E6578744C76457870223A3130302C224641555F504D5F57696E73223A302C224641555F57696E436861696E4D6178223A302C224641555F57696E436861696E4E6F77223A302C2247494F5F426164676531223A3530333030392C2247494F5F4261646765315F56616C223A3333312C2247494F5F426164676532223A3530313030332C2247494F5F4261646765325F56616C223A3839332C2247494F5F426164676533223A3530323133392C2247494F5F4261646765335F56616C223A3839332C2247494F5F457870223A31383031373236302C2247494F5F4C76223A3839332C2247494F5F4E6578744C76457870223A31383034323530302C2247494F5F504D5F57696E73223A302C2247494F5F57696E436861696E4D6178223A35332C2247494F5F57696E436861696E4E6F77223A372C22474C445F426164676531223A323130332C22474C445F4261646765315F56616C223A392C22474C445F426164676532223A3530343030302C22474C445F4261646765325F56616C223A302C22474C445F426164676533223A3530313030302C22474C445F4261646765335F56616C223A312C22474C445F457870223A302C22474C445F4C76223A312C22474C445F4E6578744C76457870223A3130302C22474C445F504D5F57696E73223A302C22474C445F57696E436861696E4D6178223A302C22474C445F57696E436861696E4E6F77223A302C22494E4F5F426164676531223A323130332C22494E4F5F4261646765315F56616C223A392C22494E4F5F426164676532223A3530343030302C22494E4F5F4261646765325F56616C223A302C22494E4F5F426164676533223A3530313030302C22494E4F5F4261646765335F56616C223A312C22494E4F5F457870223A302C22494E4F5F4C76223A312C22494E4F5F4E6578744C76457870223A3130302C22494E4F5F504D5F57696E73223A302C22494E4F5F57696E436861696E4D6178223A302C22494E4F5F57696E436861696E4E6F77223A302C224A4B4F5F426164676531223A323130332C224A4B4F5F4261646765315F56616C223A392C224A4B4F5F426164676532223A3530343030302C224A4B4F5F4261646765325F56616C223A302C224A4B4F5F426164676533223A3530313030302C224A4B4F5F4261646765335F56616C223A312C224A4B4F5F457870223A302C224A4B4F5F4C76223A312C224A4B4F5F4E6578744C76457870223A3130302C224A4B4F5F504D5F57696E73223A302C224A4B4F5F57696E436861696E4D6178223A302C224A4B4F5F57696E436861696E4E6F77223A302C224B594B5F426164676531223A323130332C224B594B5F4261646765315F56616C223A392C224B594B5F426164676532223A3530343030302C224B594B5F4261646765325F56616C223A302C224B594B5F426164676533223A3530313030302C224B594B5F4261646765335F56616C223A312C224B594B5F457870223A302C224B594B5F4C76223A312C224B594B5F4E6578744C76457870223A3130302C224B594B5F504D5F57696E73223A302C224B594B5F57696E436861696E4D6178223A302C224B594B5F57696E436861696E4E6F77223A302C224C454F5F426164676531223A323130332C224C454F5F4261646765315F56616C223A392C224C454F5F426164676532223A3530343030302C224C454F5F4261646765325F56616C223A302C224C454F5F426164676533223A3530313030302C224C454F5F4261646765335F56616C223A312C224C454F5F457870223A302C224C454F5F4C76223A312C224C454F5F4E6578744C76457870223A3130302C224C454F5F504D5F57696E73223A302C224C454F5F57696E436861696E4D6178223A302C224C454F5F57696E436861696E4E6F77223A302C224C6F62627952616E6B223A392C224C6F6262795475746F7269616C223A312C224D41595F426164676531223A323130332C224D41595F4261646765315F56616C223A392C224D41595F426164676532223A3530343030302C224D41595F4261646765325F56616C223A302C224D41595F426164676533223A3530313030302C224D41595F4261646765335F56616C223A312C224D41595F457870223A302C224D41595F4C76223A312C224D41595F4E6578744C76457870223A3130302C224D41595F504D5F57696E73223A302C224D41595F57696E436861696E4D6178223A302C224D41595F57696E436861696E4E6F77223A302C224D4C4C5F426164676531223A323130332C224D4C4C5F4261646765315F56616C223A392C224D4C4C5F426164676532223A3530343030302C224D4C4C5F4261646765325F56616C223A302C224D4C4C5F426164676533223A3530313030302C224D4C4C5F4261646765335F56616C223A312C224D4C4C5F457870223A302C224D4C4C5F4C76223A312C224D4C4C5F4E6578744C76457870223A3130302C224D4C4C5F504D5F57696E73223A302C224D4C4C5F57696E436861696E4D6178223A302C224D4C4C5F57696E436861696E4E6F77223A302C224D61784C6F62627952616E6B223A392C224D6178566970537461747573223A322C224D79526F6F6D48696464656E223A302C224E41475F426164676531223A323130332C224E41475F4261646765315F56616C223A392C224E41475F426164676532223A3530343030302C224E41475F4261646765325F56616C223A302C224E41475F426164676533223A3530313030302C224E41475F4261646765335F56616C223A312C224E41475F457870223A302C224E41475F4C76223A312C224E41475F4E6578744C76457870223A3130302C224E41475F504D5F57696E73223A302C224E41475F57696E436861696E4D6178223A302C224E41475F57696E436861696E4E6F77223A302C224E616D6541757261223A302C224E616D65417572615465726D223A302C224E69636B4E616D65223A22474720506C61796572222C224E6F74426567696E6E6572223A302C224F6E6C696E6543686561745074223A35302C224F6E6C696E654944223A22313130303030313030303265393565222C22504F545F426164676531223A323130332C22504F545F4261646765315F56616C223A392C22504F545F426164676532223A3530343030302C22504F545F4261646765325F56616C223A302C22504F545F426164676533223A3530313030302C22504F545F4261646765335F56616C223A312C22504F545F457870223A302C22504F545F4C76223A312C22504F545F4E6578744C76457870223A3130302C22504F545F504D5F57696E73223A302C22504F545F57696E436861696E4D6178223A302C22504F545F57696E436861696E4E6F77223A302C22506C617956657273696F6E223A3130322C22506C6179657257696E436861696E4D6178223A3131382C22506C6179657257696E436861696E4E6F77223A31302C22507265764C6F62627952616E6B223A392C2250726576566970537461747573223A322C225075626C6963436F6D6D656E74223A22476F6F64206C75636B21222C2252414D5F426164676531223A323130332C2252414D5F4261646765315F56616C223A392C2252414D5F426164676532223A3530343030322C2252414D5F4261646765325F56616C223A3132382C2252414D5F426164676533223A3530313030332C2252414D5F4261646765335F56616C223A3433392C2252414D5F457870223A353331353339302C2252414D5F4C76223A3433392C2252414D5F4E6578744C76457870223A353332323530302C2252414D5F504D5F57696E73223A302C2252414D5F57696E436861696E4D6178223A35362C2252414D5F57696E436861696E4E6F77223A31322C2252616E6B436865636B4D61746368223A302C2252616E6B436865636B5074223A302C2252616E6B436865636B54657374223A372C22534F4C5F426164676531223A323130332C22534F4C5F4261646765315F56616C223A392C22534F4C5F426164676532223A3530343030302C22534F4C5F4261646765325F56616C223A302C22534F4C5F426164676533223A3530313030302C22534F4C5F4261646765335F56616C223A312C22534F4C5F457870223A302C22534F4C5F4C76223A312C22534F4C5F4E6578744C76457870223A3130302C22534F4C5F504D5F57696E73223A302C22534F4C5F57696E436861696E4D6178223A302C22534F4C5F57696E436861696E4E6F77223A302C2253656C65637442474D223A302C2253656C6563744368617261223A302C2253656C6563744368617261436F6C6F72223A302C2253656C6563745374616765223A302C22546F74616C506C617954696D65223A33303938393438312C22546F74616C52616E6B4D61746368223A323032302C225570646174655F446179223A31332C225570646174655F486F7572223A31342C225570646174655F4D696E223A31322C225570646174655F4D6F6E7468223A322C225570646174655F59656172223A323032322C22557365724944223A3232303132303031303832323138393937392C22566970436865636B4D61746368223A302C22566970436865636B5074223A302C22566970537461747573223A322C22576F726C64446F6C6C6172223A3430393430302C22576F726C64446F6C6C6172546F74616C223A3530323030302C225A41545F426164676531223A323130332C225A41545F4261646765315F56616C223A392C225A41545F426164676532223A3530343030302C225A41545F4261646765325F56616C223A302C225A41545F426164676533223A3530313030302C225A41545F4261646765335F56616C223A312C225A41545F457870223A302C225A41545F4C76223A312C225A41545F4E6578744C76457870223A3130302C225A41545F504D5F57696E73223A302C225A41545F57696E436861696E4D6178223A302C225A41545F57696E436861696E4E6F77223A307D";
+
+    // A replay response with ten replays
+    pub(crate) const RESPONSE_2: &[u8] = b"\x92\x98\xad61ff0f60da094\0\xb32022/02/05 23:59:28\xa50.1.0\xa50.0.2\xa50.0.2\xa0\xa0\x94\0\0\n\x9a\x9d\xcf\x03\x0eS}\x9f\x8ds\xbf\t\x08\x0c\x0b\x95\xb2210611232517053199\xa5limon\xb176561198082398187\xaf1100001074797eb\x06\x95\xb2210818223745601103\xafSamuraiPizzaCat\xb176561199149925226\xaf110000146e8c36a\x07\x02\xb32022-02-06 04:07:59\x01\0\0\0\x9d\xcf\x03\x0eS|v\xbc6N\t\x08\x11\x0c\x95\xb2210905181006143473\xa8Haratura\xb176561198148293594\xaf11000010b3513da\x07\x95\xb2210611232517053199\xa5limon\xb176561198082398187\xaf1100001074797eb\x06\x01\xb32022-02-06 03:58:19\x01\0\0\0\x9d\xcf\x03\x0eS|lr}\xc1\t\x08\x11\x0c\x95\xb2210905181006143473\xa8Haratura\xb176561198148293594\xaf11000010b3513da\x07\x95\xb2210611232517053199\xa5limon\xb176561198082398187\xaf1100001074797eb\x06\x01\xb32022-02-06 03:56:46\x01\0\0\0\x9d\xcf\x03\x0eS|du\xac>\t\x08\x11\x0c\x95\xb2210905181006143473\xa8Haratura\xb176561198148293594\xaf11000010b3513da\x07\x95\xb2210611232517053199\xa5limon\xb176561198082398187\xaf1100001074797eb\x06\x01\xb32022-02-06 03:55:12\x01\x01\0\0\x9d\xcf\x03\x0eSy?\x93\x83\x86\t\x06\x04\0\x95\xb2210825010040078270\xacKenoMcsteamo\xb176561198354688358\xaf110000117826966\x05\x95\xb2211128031436376804\xa9BundleBox\xb176561198103224698\xaf11000010885617a\x05\x01\xb32022-02-06 03:29:31\x01\0\0\0\x9d\xcf\x03\x0eSy/\xfbL\xaa\t\x06\x04\0\x95\xb2210825010040078270\xacKenoMcsteamo\xb176561198354688358\xaf110000117826966\x05\x95\xb2211128031436376804\xa9BundleBox\xb176561198103224698\xaf11000010885617a\x05\x01\xb32022-02-06 03:27:10\x01\0\0\0\x9d\xcf\x03\x0eSy\"\xfc\x1d\x85\t\x06\x04\0\x95\xb2210825010040078270\xacKenoMcsteamo\xb176561198354688358\xaf110000117826966\x05\x95\xb2211128031436376804\xa9BundleBox\xb176561198103224698\xaf11000010885617a\x05\x02\xb32022-02-06 03:24:52\x01\0\0\0\x9d\xcf\x03\x0eSx\xf9\x8c\xd2\r\t\x06\x04\x12\x95\xb2210825010040078270\xacKenoMcsteamo\xb176561198354688358\xaf110000117826966\x05\x95\xb2210719021019879063\xa9Sebastard\xb176561198354593280\xaf11000011780f600\x05\x01\xb32022-02-06 03:17:56\x01\0\0\0\x9d\xcf\x03\x0eSx\xedf\x1f\xf4\t\x06\x04\x12\x95\xb2210825010040078270\xacKenoMcsteamo\xb176561198354688358\xaf110000117826966\x05\x95\xb2210719021019879063\xa9Sebastard\xb176561198354593280\xaf11000011780f600\x05\x01\xb32022-02-06 03:15:53\x01\0\0\0\x9d\xcf\x03\x0eS{q&\x8d\x92\t\x07\x05\x0c\x95\xb2220117205818084945\xa8Bugabalu\xb176561198136737187\xaf11000010a84bda3\x05\x95\xb2210611232517053199\xa5limon\xb176561198082398187\xaf1100001074797eb\x06\x02\xb32022-02-06 03:14:30\x01\0\0\0";
+
+    // A replay response without any replays
+    const EMPTY_RESPONSE: &[u8] = b"\x92\x98\xad61ff0f60da094\0\xb32022/02/05 23:59:28\xa50.1.0\xa50.0.2\xa50.0.2\xa0\xa0\x94\0\0\n\x90";
+
+    pub(crate) fn http_response(status: &str, body: &[u8]) -> Vec<u8> {
+        let mut response = format!(
+            "HTTP/1.1 {}\r\ncontent-length: {}\r\nconnection: close\r\n\r\n",
+            status,
+            body.len()
+        )
+        .into_bytes();
+        response.extend_from_slice(body);
+        response
+    }
+
+    // Serve one canned http response per connection, in order. Returns the base url of the server.
+    pub(crate) async fn serve(responses: Vec<Vec<u8>>) -> String {
+        serve_recording(responses).await.0
+    }
+
+    // Same as serve() but also returns the requests received so far as lossy utf8
+    async fn serve_recording(responses: Vec<Vec<u8>>) -> (String, Arc<Mutex<Vec<String>>>) {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let requests = Arc::new(Mutex::new(vec![]));
+        let received = requests.clone();
+        tokio::spawn(async move {
+            for response in responses {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                // Read the whole request before answering
+                let mut request = vec![];
+                let mut buf = [0; 1024];
+                loop {
+                    let n = socket.read(&mut buf).await.unwrap();
+                    request.extend_from_slice(&buf[..n]);
+                    let text = String::from_utf8_lossy(&request).to_lowercase();
+                    if let Some(end) = text.find("\r\n\r\n") {
+                        let length = text
+                            .lines()
+                            .find_map(|l| l.strip_prefix("content-length: "))
+                            .map_or(0, |l| l.trim().parse::<usize>().unwrap());
+                        if request.len() >= end + 4 + length {
+                            break;
+                        }
+                    }
+                    if n == 0 {
+                        break;
+                    }
+                }
+                received
+                    .lock()
+                    .unwrap()
+                    .push(String::from_utf8_lossy(&request).into_owned());
+                socket.write_all(&response).await.unwrap();
+                socket.shutdown().await.unwrap();
+            }
+        });
+        (format!("http://{}", addr), requests)
+    }
+
+    // Answers every request with an empty page and records when it was sent. Unlike serve() it
+    // does no io, so tests with paused time advance the clock only while waiting on timers.
+    #[derive(Default)]
+    struct SendTimes(Mutex<Vec<Instant>>);
+
+    impl Transport for SendTimes {
+        fn post<'a>(&'a self, _: &'a str, _: &'a str) -> BoxFuture<'a, Result<Vec<u8>>> {
+            self.0.lock().unwrap().push(Instant::now());
+            Box::pin(async { Ok(EMPTY_RESPONSE.to_vec()) })
+        }
+    }
+
+    impl SendTimes {
+        // The time every request was sent at, relative to `start`
+        fn since(&self, start: Instant) -> Vec<Duration> {
+            self.0.lock().unwrap().iter().map(|&t| t - start).collect()
+        }
+    }
+
+    #[tokio::test]
+    async fn retry_server_errors() {
+        let url = serve(vec![
+            http_response("500 Internal Server Error", b""),
+            http_response("503 Service Unavailable", b""),
+            http_response("200 OK", EMPTY_RESPONSE),
+        ])
+        .await;
+
+        let ctx = Context::new(url).retries(3, Duration::from_millis(1));
+        let (matches, errors) =
+            get_replays_page(&ctx, Platform::PC, 0, 10, &QueryParameters::default())
+                .await
+                .unwrap();
+        assert_eq!(matches.count(), 0);
+        assert_eq!(errors.count(), 0);
+    }
+
+    #[tokio::test]
+    async fn retries_exhausted() {
+        let url = serve(vec![
+            http_response("500 Internal Server Error", b""),
+            http_response("500 Internal Server Error", b""),
+        ])
+        .await;
+
+        let ctx = Context::new(url).retries(2, Duration::from_millis(1));
+        let result = get_replays_page(&ctx, Platform::PC, 0, 10, &QueryParameters::default()).await;
+        assert!(matches!(result, Err(Error::ReqwestError(_))));
+    }
+
+    #[tokio::test]
+    async fn all_floors() {
+        let mut responses = vec![http_response("200 OK", EMPTY_RESPONSE); 10];
+        responses.push(http_response("500 Internal Server Error", b""));
+        let url = serve(responses).await;
+
+        let ctx = Context::new(url);
+        let floors = get_all_floors(&ctx, Platform::PC, 10, 4).await.unwrap();
+        assert_eq!(floors.keys().copied().collect::<Vec<_>>(), Floor::all());
+        // The failed request can belong to any floor since they are sent concurrently
+        assert_eq!(floors.values().filter(|r| r.is_err()).count(), 1);
+        assert!(floors.values().flatten().all(|m| m.is_empty()));
+    }
+
+    #[tokio::test]
+    async fn cached_responses() {
+        let url = serve(vec![http_response("200 OK", RESPONSE_2)]).await;
+
+        let ctx = Context::new(url).cache(Duration::from_secs(60));
+        let query = QueryParameters::default();
+        for _ in 0..2 {
+            let (matches, _) = get_replays_page(&ctx, Platform::PC, 0, 10, &query)
+                .await
+                .unwrap();
+            assert_eq!(matches.count(), 10);
+        }
+        // The server only answers once, so this request has to fail without the cache
+        ctx.clear_cache();
+        assert!(get_replays_page(&ctx, Platform::PC, 0, 10, &query)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn cache_skips_invalid_and_expired() {
+        // Answers with the responses in order, without io so only advance() moves the clock
+        struct Responses(Mutex<VecDeque<&'static [u8]>>);
+
+        impl Transport for Responses {
+            fn post<'a>(&'a self, _: &'a str, _: &'a str) -> BoxFuture<'a, Result<Vec<u8>>> {
+                let response = self.0.lock().unwrap().pop_front().unwrap();
+                Box::pin(async move { Ok(response.to_vec()) })
+            }
+        }
+
+        let transport = Responses(Mutex::new(VecDeque::from([
+            b"garbage".as_slice(),
+            EMPTY_RESPONSE,
+            RESPONSE_2,
+        ])));
+        let ctx = Context::new("http://recorded".into())
+            .with_transport(Arc::new(transport))
+            .cache(Duration::from_millis(50));
+        let query = QueryParameters::default();
+        let (_, errors) = get_replays_page(&ctx, Platform::PC, 0, 10, &query)
+            .await
+            .unwrap();
+        assert_eq!(errors.count(), 1);
+        let (matches, _) = get_replays_page(&ctx, Platform::PC, 0, 10, &query)
+            .await
+            .unwrap();
+        assert_eq!(matches.count(), 0);
+        tokio::time::advance(Duration::from_millis(49)).await;
+        let (matches, _) = get_replays_page(&ctx, Platform::PC, 0, 10, &query)
+            .await
+            .unwrap();
+        assert_eq!(matches.count(), 0);
+        tokio::time::advance(Duration::from_millis(1)).await;
+        let (matches, _) = get_replays_page(&ctx, Platform::PC, 0, 10, &query)
+            .await
+            .unwrap();
+        assert_eq!(matches.count(), 10);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn cache_prunes_and_checks_status() {
+        let cache = Cache {
+            ttl: Duration::from_secs(1),
+            entries: Mutex::new(HashMap::new()),
+        };
+        cache.insert("a".into(), bytes::Bytes::from_static(RESPONSE_2));
+        tokio::time::advance(Duration::from_secs(2)).await;
+        cache.insert("b".into(), bytes::Bytes::from_static(RESPONSE_2));
+        assert_eq!(
+            cache.entries.lock().unwrap().keys().collect::<Vec<_>>(),
+            ["b"]
+        );
+
+        // Set the result code in the header to an error
+        let mut response = rmpv::decode::read_value(&mut &RESPONSE_2[..]).unwrap();
+        if let rmpv::Value::Array(fields) = &mut response {
+            if let rmpv::Value::Array(header) = &mut fields[0] {
+                header[1] = rmpv::Value::from(1);
+            }
+        }
+        let mut error = vec![];
+        rmpv::encode::write_value(&mut error, &response).unwrap();
+        cache.insert("c".into(), error.into());
+        assert!(cache.get("c").is_none());
+    }
+
+    #[tokio::test]
+    async fn batched_users() {
+        let url = serve(vec![
+            http_response("200 OK", &from_hex(STATISTICS_RESPONSE)),
+            http_response("500 Internal Server Error", b""),
+        ])
+        .await;
+
+        let ctx = Context::new(url);
+        let users = get_users(&ctx, Platform::PC, &[1, 2]).await;
+        assert_eq!(users.len(), 2);
+        // Either request can get the error since they are sent concurrently
+        let user = users.iter().find_map(|r| r.as_ref().ok()).unwrap();
+        assert_eq!(user.name, "GG Player");
+        assert_eq!(users.iter().filter(|r| r.is_err()).count(), 1);
+    }
+
+    #[tokio::test]
+    async fn invalid_arguments() {
+        let ctx = Context::new("http://unused".into());
+        let query = QueryParameters::default();
+        for (page, replays_per_page, message) in [
+            (
+                0,
+                0,
+                "replays per page have to be between 1 and 127, queried 0",
+            ),
+            (
+                0,
+                128,
+                "replays per page have to be between 1 and 127, queried 128",
+            ),
+            (100, 10, "page has to be between 0 and 99, queried 100"),
+        ] {
+            match get_replays_page(&ctx, Platform::PC, page, replays_per_page, &query).await {
+                Err(Error::InvalidArgument(m)) => assert_eq!(m, message),
+                r => panic!("expected an invalid argument, got {:?}", r.err()),
+            }
+        }
+
+        // 0xff would be sent as -1, which means no character
+        let query = QueryParameters::default()
+            .character(Character::Sol)
+            .character(Character::Unknown(0xff));
+        match get_replays_page(&ctx, Platform::PC, 0, 10, &query).await {
+            Err(Error::InvalidArgument(m)) => {
+                assert_eq!(m, "cannot filter on unknown character code 0xff")
+            }
+            r => panic!("expected an invalid argument, got {:?}", r.err()),
+        }
+    }
+
+    #[tokio::test]
+    async fn empty_result() {
+        let url = serve(vec![http_response("200 OK", EMPTY_RESPONSE); 2]).await;
+
+        let ctx = Context::new(url);
+        let (matches, errors) = get_replays(
+            &ctx,
+            Platform::PC,
+            2,
+            10,
+            QueryParameters::default().floor(Floor::F1),
+        )
+        .await
+        .unwrap();
+        assert_eq!(matches.count(), 0);
+        assert_eq!(errors.count(), 0);
+    }
+
+    #[tokio::test]
+    async fn server_unavailable() {
+        let url = serve(vec![http_response("503 Service Unavailable", b"")]).await;
+
+        let ctx = Context::new(url);
+        let result = get_replays_page(&ctx, Platform::PC, 0, 10, &QueryParameters::default()).await;
+        assert!(matches!(result, Err(Error::ServerUnavailable)));
+    }
+
+    #[tokio::test]
+    async fn request_metrics() {
+        let url = serve(vec![
+            http_response("503 Service Unavailable", b""),
+            http_response("200 OK", RESPONSE_2),
+        ])
+        .await;
+        let metrics = Arc::new(Mutex::new(vec![]));
+        let recorded = metrics.clone();
+        let ctx = Context::new(url.clone())
+            .retries(2, Duration::from_millis(1))
+            .cache(Duration::from_secs(60))
+            .on_request(move |m| recorded.lock().unwrap().push(m.clone()));
+        for _ in 0..2 {
+            let (matches, _) =
+                get_replays_page(&ctx, Platform::PC, 0, 10, &QueryParameters::default())
+                    .await
+                    .unwrap();
+            assert_eq!(matches.count(), 10);
+        }
+
+        let metrics = metrics.lock().unwrap();
+        assert_eq!(metrics.len(), 1);
+        assert_eq!(metrics[0].url, url + "/api/catalog/get_replay");
+        assert_eq!(metrics[0].status, Some(200));
+        assert_eq!(metrics[0].bytes, RESPONSE_2.len());
+        assert_eq!(metrics[0].retries, 1);
+    }
+
+    #[tokio::test]
+    async fn rate_limited() {
+        let too_many_requests = b"HTTP/1.1 429 Too Many Requests\r\nretry-after: 2\r\n\
+            content-length: 0\r\nconnection: close\r\n\r\n";
+        let url = serve(vec![too_many_requests.to_vec()]).await;
+        let result = get_replays_page(
+            &Context::new(url),
+            Platform::PC,
+            0,
+            10,
+            &QueryParameters::default(),
+        )
+        .await;
+        assert!(matches!(
+            result,
+            Err(Error::RateLimited {
+                retry_after: Some(d)
+            }) if d == Duration::from_secs(2)
+        ));
+
+        let url = serve(vec![
+            http_response("429 Too Many Requests", b""),
+            http_response("200 OK", RESPONSE_2),
+        ])
+        .await;
+        let ctx = Context::new(url).retries(2, Duration::from_millis(1));
+        let (matches, _) = get_replays_page(&ctx, Platform::PC, 0, 10, &QueryParameters::default())
+            .await
+            .unwrap();
+        assert_eq!(matches.count(), 10);
+    }
+
+    #[test]
+    fn retry_delay_bounds() {
+        let base = Duration::from_millis(500);
+        assert_eq!(retry_delay(base, 0, &Error::ServerUnavailable), base);
+        assert_eq!(retry_delay(base, 2, &Error::ServerUnavailable), base * 4);
+        assert_eq!(
+            retry_delay(Duration::from_secs(1), 39, &Error::ServerUnavailable),
+            MAX_BACKOFF
+        );
+        assert_eq!(
+            retry_delay(Duration::MAX, 1, &Error::ServerUnavailable),
+            MAX_BACKOFF
+        );
+
+        let rate_limited = |secs| Error::RateLimited {
+            retry_after: Some(Duration::from_secs(secs)),
+        };
+        assert_eq!(
+            retry_delay(base, 0, &rate_limited(2)),
+            Duration::from_secs(2)
+        );
+        assert_eq!(
+            retry_delay(base, 0, &rate_limited(99999999)),
+            MAX_RETRY_AFTER
+        );
+    }
+
+    #[test]
+    fn retry_after() {
+        assert_eq!(parse_retry_after("120"), Some(Duration::from_secs(120)));
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(parse_retry_after("soon"), None);
+    }
+
+    #[tokio::test]
+    async fn maintenance_page() {
+        // A stand-in for the maintenance page, which is served with status 200
+        const PAGE: &[u8] = include_bytes!("../test_data/maintenance_response.html");
+        let url = serve(vec![http_response("200 OK", PAGE)]).await;
+
+        let ctx = Context::new(url);
+        let result = get_replays_page(&ctx, Platform::PC, 0, 10, &QueryParameters::default()).await;
+        assert!(matches!(result, Err(Error::ServerUnavailable)));
+
+        assert!(is_maintenance_page(b"  Server maintenance in progress"));
+        assert!(!is_maintenance_page(b"garbage"));
+        assert!(!is_maintenance_page(RESPONSE_2));
+    }
+
+    #[tokio::test]
+    async fn no_retry_on_parse_error() {
+        let url = serve(vec![
+            http_response("200 OK", b"garbage"),
+            http_response("200 OK", EMPTY_RESPONSE),
+        ])
+        .await;
+
+        let ctx = Context::new(url).retries(3, Duration::from_millis(1));
+        let (_, errors) = get_replays_page(&ctx, Platform::PC, 0, 10, &QueryParameters::default())
+            .await
+            .unwrap();
+        assert_eq!(errors.count(), 1);
+    }
+
+    #[tokio::test]
+    async fn stream_replays() {
+        use futures_util::StreamExt;
+
+        let url = serve(vec![
+            http_response("200 OK", RESPONSE_2),
+            http_response("200 OK", EMPTY_RESPONSE),
+        ])
+        .await;
+
+        let ctx = Context::new(url);
+        let matches = replay_stream(&ctx, Platform::PC, 10, QueryParameters::default())
+            .unwrap()
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(matches.len(), 10);
+        assert!(matches.iter().all(|m| m.is_ok()));
+    }
+
+    #[tokio::test]
+    async fn recent_matches() {
+        let url = serve(vec![
+            http_response("200 OK", RESPONSE_2),
+            http_response("200 OK", RESPONSE_2),
+            http_response("200 OK", EMPTY_RESPONSE),
+        ])
+        .await;
+
+        let ctx = Context::new(url);
+        let matches = get_recent_matches(&ctx, Platform::PC, Floor::Celestial, 20)
+            .await
+            .unwrap();
+        // The second page only contains duplicates of the first one
+        assert_eq!(matches.len(), 10);
+        assert!(matches
+            .windows(2)
+            .all(|w| w[0].timestamp() >= w[1].timestamp()));
+    }
+
+    #[tokio::test]
+    async fn recent_matches_limit() {
+        let url = serve(vec![http_response("200 OK", RESPONSE_2)]).await;
+
+        let ctx = Context::new(url);
+        let newest = get_recent_matches(&ctx, Platform::PC, Floor::Celestial, 3)
+            .await
+            .unwrap();
+        let mut matches = BTreeSet::new();
+        parse_response_from_bytes(&mut matches, &mut vec![], RESPONSE_2);
+        let expected = matches.into_iter().rev().take(3).collect::<Vec<_>>();
+        assert_eq!(newest, expected);
+    }
+
+    #[tokio::test]
+    async fn base_url_override() {
+        let (url, requests) = serve_recording(vec![http_response("200 OK", RESPONSE_2)]).await;
+
+        let ctx = Context::default()
+            .timeout(Duration::from_secs(5))
+            .base_url(url + "/");
+        let (matches, _) = get_replays_page(&ctx, Platform::PC, 0, 10, &QueryParameters::default())
+            .await
+            .unwrap();
+        assert_eq!(matches.count(), 10);
+        assert!(requests.lock().unwrap()[0].starts_with("POST /api/catalog/get_replay "));
+    }
+
+    #[tokio::test]
+    async fn player_replays() {
+        let url = serve(vec![
+            http_response("200 OK", RESPONSE_2),
+            http_response("200 OK", EMPTY_RESPONSE),
+        ])
+        .await;
+
+        let (all, _) = parse_matches(RESPONSE_2).unwrap();
+        let all = all.collect::<Vec<_>>();
+        let id = all[0].player1().id;
+        let expected = all
+            .into_iter()
+            .rev()
+            .filter(|m| m.contains_player(id))
+            .collect::<Vec<_>>();
+
+        let ctx = Context::new(url);
+        let matches = get_player_replays(&ctx, Platform::PC, id, 10, QueryParameters::default())
+            .await
+            .unwrap();
+        assert!(!matches.is_empty());
+        assert_eq!(matches, expected);
+    }
+
+    #[tokio::test]
+    async fn replays_between() {
+        let url = serve(vec![http_response("200 OK", RESPONSE_2)]).await;
+
+        let ctx = Context::new(url);
+        let start = Utc.with_ymd_and_hms(2022, 2, 5, 18, 30, 0).unwrap();
+        let end = Utc.with_ymd_and_hms(2022, 2, 5, 19, 0, 0).unwrap();
+        // The page already reaches back past start, so no second page is requested
+        let (matches, errors) =
+            get_replays_between(&ctx, Platform::PC, start, end, QueryParameters::default())
+                .await
+                .unwrap();
+        let timestamps = matches.map(|m| *m.timestamp()).collect::<Vec<_>>();
+        assert_eq!(timestamps.len(), 3);
+        assert!(timestamps.iter().all(|t| (start..end).contains(t)));
+        assert_eq!(errors.count(), 0);
+    }
+
+    #[tokio::test]
+    async fn replays_between_exhausted() {
+        let url = serve(vec![
+            http_response("200 OK", RESPONSE_2),
+            http_response("200 OK", EMPTY_RESPONSE),
+        ])
+        .await;
+
+        let ctx = Context::new(url);
+        let start = Utc.with_ymd_and_hms(2022, 2, 1, 0, 0, 0).unwrap();
+        let (matches, _) = get_replays_between(
+            &ctx,
+            Platform::PC,
+            start,
+            Utc::now(),
+            QueryParameters::default(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(matches.count(), 10);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn rate_limit() {
+        let transport = Arc::new(SendTimes::default());
+        let ctx = Context::new("http://recorded".into())
+            .with_transport(transport.clone())
+            .rate_limit(10);
+        let start = Instant::now();
+        let (matches, _) = get_replays(&ctx, Platform::PC, 3, 10, QueryParameters::default())
+            .await
+            .unwrap();
+        assert_eq!(matches.count(), 0);
+        // The first request is sent right away and the other two wait 100ms each
+        assert_eq!(
+            transport.since(start),
+            [0, 100, 200].map(Duration::from_millis)
+        );
+    }
+
+    #[tokio::test]
+    async fn concurrent_pages() {
+        let url = serve(vec![
+            http_response("200 OK", RESPONSE_2),
+            http_response("200 OK", RESPONSE_2),
+            http_response("200 OK", EMPTY_RESPONSE),
+        ])
+        .await;
+
+        let ctx = Context::new(url);
+        let (matches, errors) =
+            get_replays_concurrent(&ctx, Platform::PC, 3, 10, QueryParameters::default(), 2)
+                .await
+                .unwrap();
+        let matches = matches.collect::<Vec<_>>();
+        assert_eq!(matches.len(), 10);
+        assert!(matches
+            .windows(2)
+            .all(|w| w[0].timestamp() <= w[1].timestamp()));
+        assert_eq!(errors.count(), 0);
+    }
+
+    #[tokio::test]
+    async fn custom_transport() {
+        struct Recorded(Mutex<Vec<String>>);
+
+        impl Transport for Recorded {
+            fn post<'a>(&'a self, url: &'a str, _: &'a str) -> BoxFuture<'a, Result<Vec<u8>>> {
+                self.0.lock().unwrap().push(url.to_string());
+                Box::pin(async { Ok(RESPONSE_2.to_vec()) })
+            }
+        }
+
+        let transport = Arc::new(Recorded(Mutex::new(vec![])));
+        let ctx = Context::new("http://recorded".into()).with_transport(transport.clone());
+        let (matches, errors) = get_replays(&ctx, Platform::PC, 2, 10, QueryParameters::default())
+            .await
+            .unwrap();
+        assert_eq!(matches.count(), 10);
+        assert_eq!(errors.count(), 0);
+        assert_eq!(
+            *transport.0.lock().unwrap(),
+            ["http://recorded/api/catalog/get_replay"; 2]
+        );
+    }
+
+    #[cfg(feature = "gzip")]
+    #[tokio::test]
+    async fn gzip_response() {
+        use std::io::Write;
+
+        let mut encoder = flate2::write::GzEncoder::new(vec![], flate2::Compression::default());
+        encoder.write_all(RESPONSE_2).unwrap();
+        let body = encoder.finish().unwrap();
+        let mut response = format!(
+            "HTTP/1.1 200 OK\r\ncontent-encoding: gzip\r\ncontent-length: {}\r\nconnection: close\r\n\r\n",
+            body.len()
+        )
+        .into_bytes();
+        response.extend_from_slice(&body);
+        let (url, requests) = serve_recording(vec![response]).await;
+
+        let ctx = Context::new(url);
+        let bytes = get_replays_raw(&ctx, Platform::PC, 0, 10, &QueryParameters::default())
+            .await
+            .unwrap();
+        assert_eq!(bytes, RESPONSE_2);
+        assert!(requests.lock().unwrap()[0].contains("accept-encoding: gzip\r\n"));
+    }
+
+    #[tokio::test]
+    async fn custom_headers() {
+        let (url, requests) =
+            serve_recording(vec![http_response("200 OK", EMPTY_RESPONSE); 2]).await;
+
+        let ctx = Context::new(url.clone());
+        get_replays_raw(&ctx, Platform::PC, 0, 10, &QueryParameters::default())
+            .await
+            .unwrap();
+        let ctx = Context::new(url)
+            .user_agent(header::HeaderValue::from_static("my-scraper/1.0"))
+            .header(
+                header::HeaderName::from_static("x-contact"),
+                header::HeaderValue::from_static("me@example.com"),
+            );
+        get_replays_raw(&ctx, Platform::PC, 0, 10, &QueryParameters::default())
+            .await
+            .unwrap();
+
+        let requests = requests.lock().unwrap();
+        assert!(requests[0].contains("user-agent: Steam\r\n"));
+        assert!(requests[1].contains("user-agent: my-scraper/1.0\r\n"));
+        assert!(!requests[1].contains("Steam"));
+        assert!(requests[1].contains("x-contact: me@example.com\r\n"));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn cancel_rate_limited_request() {
+        let transport = Arc::new(SendTimes::default());
+        let ctx = Context::new("http://recorded".into())
+            .with_transport(transport.clone())
+            .rate_limit(2);
+        let query = QueryParameters::default();
+        let start = Instant::now();
+        get_replays_raw(&ctx, Platform::PC, 0, 10, &query)
+            .await
+            .unwrap();
+        let cancelled = tokio::time::timeout(
+            Duration::from_millis(10),
+            get_replays_raw(&ctx, Platform::PC, 0, 10, &query),
+        )
+        .await;
+        assert!(cancelled.is_err());
+        get_replays_raw(&ctx, Platform::PC, 0, 10, &query)
+            .await
+            .unwrap();
+        // The request took the slot of the cancelled one instead of waiting for a third slot
+        assert_eq!(transport.since(start), [0, 500].map(Duration::from_millis));
+    }
+
+    #[tokio::test]
+    async fn replay_query() {
+        let (url, requests) = serve_recording(vec![
+            http_response("200 OK", RESPONSE_2),
+            http_response("200 OK", RESPONSE_2),
+        ])
+        .await;
+
+        let ctx = Context::new(url);
+        let query = ReplayQuery::new(Platform::PC)
+            .floor(Floor::Celestial)
+            .character(Character::Sol)
+            .page(2)
+            .page_size(100);
+        let (matches, _) = query.fetch(&ctx).await.unwrap();
+        assert_eq!(matches.count(), 10);
+        let parameters = QueryParameters::default()
+            .floor(Floor::Celestial)
+            .character(Character::Sol);
+        let (matches, _) = get_replays_page(&ctx, Platform::PC, 2, 100, &parameters)
+            .await
+            .unwrap();
+        assert_eq!(matches.count(), 10);
+
+        // Both send the same request body
+        let requests = requests.lock().unwrap();
+        let body = |r: &String| r.split("\r\n\r\n").nth(1).unwrap().to_string();
+        assert_eq!(body(&requests[0]), body(&requests[1]));
+    }
+
+    #[tokio::test]
+    async fn raw_response() {
+        let url = serve(vec![http_response("200 OK", RESPONSE_2)]).await;
+
+        let ctx = Context::new(url);
+        let bytes = get_replays_raw(&ctx, Platform::PC, 0, 10, &QueryParameters::default())
+            .await
+            .unwrap();
+        assert_eq!(bytes, RESPONSE_2);
+    }
+
+    #[test]
+    fn sorted_oldest_first() {
+        let (matches, _) = parse_matches(RESPONSE_2).unwrap();
+        let timestamps = matches.rev().map(|m| m.timestamp).collect::<Vec<_>>();
+        assert!(timestamps.windows(2).all(|w| w[0] >= w[1]));
+    }
+
+    #[tokio::test]
+    async fn request_timeout() {
+        // Accept connections but never answer them
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let mut sockets = vec![];
+            while let Ok((socket, _)) = listener.accept().await {
+                sockets.push(socket);
+            }
+        });
+
+        let ctx = Context::new(format!("http://{}", addr)).timeout(Duration::from_millis(50));
+        let result = get_replays_page(&ctx, Platform::PC, 0, 10, &QueryParameters::default()).await;
+        let error = match result {
+            Err(e @ Error::Timeout(_)) => e,
+            _ => panic!("expected a timeout"),
+        };
+        let source = std::error::Error::source(&error).unwrap();
+        assert!(source
+            .downcast_ref::<reqwest::Error>()
+            .unwrap()
+            .is_timeout());
+    }
+
     #[test]
     fn test_query() {
         use messagepack::*;
@@ -726,6 +2713,13 @@ mod tests {
         expect_test::expect![[r#"9295B2323131303237313133313233303038333834AD3631613565643466343631633202A5302E312E30039401007F9AFFA3416C6C016390FFFF000001"#]].assert_eq(&query.to_hex())
     }
 
+    #[test]
+    fn single_floor_query() {
+        let query = RequestQuery::from(&QueryParameters::default().floor(Floor::Celestial));
+        assert_eq!(query.min_floor, Floor::Celestial);
+        assert_eq!(query.max_floor, Floor::Celestial);
+    }
+
     #[test]
     fn decode_request() {
         let request = messagepack::ReplayRequest::from_hex("9295b2323130363131303733303536313037353337ad3631666639366131653762353902a5302e312e30039401000a9aff02016390ffff000101").unwrap();
@@ -815,9 +2809,56 @@ mod tests {
         .assert_debug_eq(&response);
     }
 
+    #[test]
+    fn unicode_comment() {
+        let comment = "よろしくお願いします 🎮\u{0}after the nul\nand a new line";
+        let mut response = Response::<StatisticsResponse>::from_hex(STATISTICS_RESPONSE).unwrap();
+        response.body.json["PublicComment"] = comment.into();
+        response.body.json["NickName"] = "名残雪⚔️".into();
+        let bytes = rmp_serde::to_vec(&response).unwrap();
+
+        let response = decode::<Response<StatisticsResponse>>(&bytes).unwrap();
+        let user = user_from_statistics(&response.body.json).unwrap();
+        assert_eq!(user.comment, comment);
+        assert_eq!(user.name, "名残雪⚔️");
+    }
+
+    #[test]
+    fn user_from_statistics_response() {
+        let response = Response::<StatisticsResponse>::from_hex(STATISTICS_RESPONSE).unwrap();
+        let user = user_from_statistics(&response.body.json).unwrap();
+        expect_test::expect![[r#"
+            User {
+                id: 220120010822189979,
+                name: "GG Player",
+                comment: "Good luck!",
+                total_ranked_matches: 2020,
+                char_stats: {
+                    Ramlethal: Stats {
+                        level: 439,
+                        win_streak: 12,
+                        max_win_streak: 56,
+                    },
+                    Giovanna: Stats {
+                        level: 893,
+                        win_streak: 7,
+                        max_win_streak: 53,
+                    },
+                    HappyChaos: Stats {
+                        level: 1546,
+                        win_streak: 10,
+                        max_win_streak: 118,
+                    },
+                },
+            }
+        "#]]
+        .assert_debug_eq(&user);
+    }
+
     #[test]
     fn statistics_response() {
-        let response = Response::<StatisticsResponse>::from_hex("9298AD3632306133393039363765346300B3323032322F30322F31342031313A31323A3039A5302E312E30A5302E302E32A5302E302E32A0A09200DA13BA7B22414E4A5F426164676531223A323130332C22414E4A5F4261646765315F56616C223A392C22414E4A5F426164676532223A3530343030302C22414E4A5F4261646765325F56616C223A302C22414E4A5F426164676533223A3530313030302C22414E4A5F4261646765335F56616C223A312C22414E4A5F457870223A302C22414E4A5F4C76223A312C22414E4A5F4E6578744C76457870223A3130302C22414E4A5F504D5F57696E73223A302C22414E4A5F57696E436861696E4D6178223A302C22414E4A5F57696E436861696E4E6F77223A302C2241584C5F426164676531223A323130332C2241584C5F4261646765315F56616C223A392C2241584C5F426164676532223A3530343030302C2241584C5F4261646765325F56616C223A302C2241584C5F426164676533223A3530313030302C2241584C5F4261646765335F56616C223A312C2241584C5F457870223A302C2241584C5F4C76223A312C2241584C5F4E6578744C76457870223A3130302C2241584C5F504D5F57696E73223A302C2241584C5F57696E436861696E4D6178223A302C2241584C5F57696E436861696E4E6F77223A302C224163636F756E744944223A37363536313139373936303435363534322C2241766174617241757261223A302C22417661746172417572615465726D223A302C22424B4E5F426164676531223A323130332C22424B4E5F4261646765315F56616C223A392C22424B4E5F426164676532223A3530343030302C22424B4E5F4261646765325F56616C223A302C22424B4E5F426164676533223A3530313030302C22424B4E5F4261646765335F56616C223A312C22424B4E5F457870223A302C22424B4E5F4C76223A312C22424B4E5F4E6578744C76457870223A3130302C22424B4E5F504D5F57696E73223A302C22424B4E5F57696E436861696E4D6178223A302C22424B4E5F57696E436861696E4E6F77223A302C224348505F426164676531223A323130332C224348505F4261646765315F56616C223A392C224348505F426164676532223A3530343030302C224348505F4261646765325F56616C223A302C224348505F426164676533223A3530313030302C224348505F4261646765335F56616C223A312C224348505F457870223A302C224348505F4C76223A312C224348505F4E6578744C76457870223A3130302C224348505F504D5F57696E73223A302C224348505F57696E436861696E4D6178223A302C224348505F57696E436861696E4E6F77223A302C22434F535F426164676531223A3530333030392C22434F535F4261646765315F56616C223A313233382C22434F535F426164676532223A3530323138392C22434F535F4261646765325F56616C223A313534362C22434F535F426164676533223A3530313030332C22434F535F4261646765335F56616C223A313534362C22434F535F457870223A37353838373135342C22434F535F4C76223A313534362C22434F535F4E6578744C76457870223A37353932323530302C22434F535F504D5F57696E73223A302C22434F535F57696E436861696E4D6178223A3131382C22434F535F57696E436861696E4E6F77223A31302C22436F6E646974696F6E426974223A2D313032352C224461746148696464656E223A312C2244656D6F7465645F4275727374223A302C2244656D6F7465645F5243223A302C2244656D6F7465645F52434D6F7665223A302C2244656D6F7465645F5243536B696C6C223A302C2244656D6F7465645F556C74696D617465223A302C2244656D6F7465645F575342223A302C224641555F426164676531223A323130332C224641555F4261646765315F56616C223A392C224641555F426164676532223A3530343030302C224641555F4261646765325F56616C223A302C224641555F426164676533223A3530313030302C224641555F4261646765335F56616C223A312C224641555F457870223A302C224641555F4C76223A312C224641555F4E6578744C76457870223A3130302C224641555F504D5F57696E73223A302C224641555F57696E436861696E4D6178223A302C224641555F57696E436861696E4E6F77223A302C2247494F5F426164676531223A3530333030392C2247494F5F4261646765315F56616C223A3333312C2247494F5F426164676532223A3530313030332C2247494F5F4261646765325F56616C223A3839332C2247494F5F426164676533223A3530323133392C2247494F5F4261646765335F56616C223A3839332C2247494F5F457870223A31383031373236302C2247494F5F4C76223A3839332C2247494F5F4E6578744C76457870223A31383034323530302C2247494F5F504D5F57696E73223A302C2247494F5F57696E436861696E4D6178223A35332C2247494F5F57696E436861696E4E6F77223A372C22474C445F426164676531223A323130332C22474C445F4261646765315F56616C223A392C22474C445F426164676532223A3530343030302C22474C445F4261646765325F56616C223A302C22474C445F426164676533223A3530313030302C22474C445F4261646765335F56616C223A312C22474C445F457870223A302C22474C445F4C76223A312C22474C445F4E6578744C76457870223A3130302C22474C445F504D5F57696E73223A302C22474C445F57696E436861696E4D6178223A302C22474C445F57696E436861696E4E6F77223A302C22494E4F5F426164676531223A323130332C22494E4F5F4261646765315F56616C223A392C22494E4F5F426164676532223A3530343030302C22494E4F5F4261646765325F56616C223A302C22494E4F5F426164676533223A3530313030302C22494E4F5F4261646765335F56616C223A312C22494E4F5F457870223A302C22494E4F5F4C76223A312C22494E4F5F4E6578744C76457870223A3130302C22494E4F5F504D5F57696E73223A302C22494E4F5F57696E436861696E4D6178223A302C22494E4F5F57696E436861696E4E6F77223A302C224A4B4F5F426164676531223A323130332C224A4B4F5F4261646765315F56616C223A392C224A4B4F5F426164676532223A3530343030302C224A4B4F5F4261646765325F56616C223A302C224A4B4F5F426164676533223A3530313030302C224A4B4F5F4261646765335F56616C223A312C224A4B4F5F457870223A302C224A4B4F5F4C76223A312C224A4B4F5F4E6578744C76457870223A3130302C224A4B4F5F504D5F57696E73223A302C224A4B4F5F57696E436861696E4D6178223A302C224A4B4F5F57696E436861696E4E6F77223A302C224B594B5F426164676531223A323130332C224B594B5F4261646765315F56616C223A392C224B594B5F426164676532223A3530343030302C224B594B5F4261646765325F56616C223A302C224B594B5F426164676533223A3530313030302C224B594B5F4261646765335F56616C223A312C224B594B5F457870223A302C224B594B5F4C76223A312C224B594B5F4E6578744C76457870223A3130302C224B594B5F504D5F57696E73223A302C224B594B5F57696E436861696E4D6178223A302C224B594B5F57696E436861696E4E6F77223A302C224C454F5F426164676531223A323130332C224C454F5F4261646765315F56616C223A392C224C454F5F426164676532223A3530343030302C224C454F5F4261646765325F56616C223A302C224C454F5F426164676533223A3530313030302C224C454F5F4261646765335F56616C223A312C224C454F5F457870223A302C224C454F5F4C76223A312C224C454F5F4E6578744C76457870223A3130302C224C454F5F504D5F57696E73223A302C224C454F5F57696E436861696E4D6178223A302C224C454F5F57696E436861696E4E6F77223A302C224C6F62627952616E6B223A392C224C6F6262795475746F7269616C223A312C224D41595F426164676531223A323130332C224D41595F4261646765315F56616C223A392C224D41595F426164676532223A3530343030302C224D41595F4261646765325F56616C223A302C224D41595F426164676533223A3530313030302C224D41595F4261646765335F56616C223A312C224D41595F457870223A302C224D41595F4C76223A312C224D41595F4E6578744C76457870223A3130302C224D41595F504D5F57696E73223A302C224D41595F57696E436861696E4D6178223A302C224D41595F57696E436861696E4E6F77223A302C224D4C4C5F426164676531223A323130332C224D4C4C5F4261646765315F56616C223A392C224D4C4C5F426164676532223A3530343030302C224D4C4C5F4261646765325F56616C223A302C224D4C4C5F426164676533223A3530313030302C224D4C4C5F4261646765335F56616C223A312C224D4C4C5F457870223A302C224D4C4C5F4C76223A312C224D4C4C5F4E6578744C76457870223A3130302C224D4C4C5F504D5F57696E73223A302C224D4C4C5F57696E436861696E4D6178223A302C224D4C4C5F57696E436861696E4E6F77223A302C224D61784C6F62627952616E6B223A392C224D6178566970537461747573223A322C224D79526F6F6D48696464656E223A302C224E41475F426164676531223A323130332C224E41475F4261646765315F56616C223A392C224E41475F426164676532223A3530343030302C224E41475F4261646765325F56616C223A302C224E41475F426164676533223A3530313030302C224E41475F4261646765335F56616C223A312C224E41475F457870223A302C224E41475F4C76223A312C224E41475F4E6578744C76457870223A3130302C224E41475F504D5F57696E73223A302C224E41475F57696E436861696E4D6178223A302C224E41475F57696E436861696E4E6F77223A302C224E616D6541757261223A302C224E616D65417572615465726D223A302C224E69636B4E616D65223A22474720506C61796572222C224E6F74426567696E6E6572223A302C224F6E6C696E6543686561745074223A35302C224F6E6C696E654944223A22313130303030313030303265393565222C22504F545F426164676531223A323130332C22504F545F4261646765315F56616C223A392C22504F545F426164676532223A3530343030302C22504F545F4261646765325F56616C223A302C22504F545F426164676533223A3530313030302C22504F545F4261646765335F56616C223A312C22504F545F457870223A302C22504F545F4C76223A312C22504F545F4E6578744C76457870223A3130302C22504F545F504D5F57696E73223A302C22504F545F57696E436861696E4D6178223A302C22504F545F57696E436861696E4E6F77223A302C22506C617956657273696F6E223A3130322C22506C6179657257696E436861696E4D6178223A3131382C22506C6179657257696E436861696E4E6F77223A31302C22507265764C6F62627952616E6B223A392C2250726576566970537461747573223A322C225075626C6963436F6D6D656E74223A22476F6F64206C75636B21222C2252414D5F426164676531223A323130332C2252414D5F4261646765315F56616C223A392C2252414D5F426164676532223A3530343030322C2252414D5F4261646765325F56616C223A3132382C2252414D5F426164676533223A3530313030332C2252414D5F4261646765335F56616C223A3433392C2252414D5F457870223A353331353339302C2252414D5F4C76223A3433392C2252414D5F4E6578744C76457870223A353332323530302C2252414D5F504D5F57696E73223A302C2252414D5F57696E436861696E4D6178223A35362C2252414D5F57696E436861696E4E6F77223A31322C2252616E6B436865636B4D61746368223A302C2252616E6B436865636B5074223A302C2252616E6B436865636B54657374223A372C22534F4C5F426164676531223A323130332C22534F4C5F4261646765315F56616C223A392C22534F4C5F426164676532223A3530343030302C22534F4C5F4261646765325F56616C223A302C22534F4C5F426164676533223A3530313030302C22534F4C5F4261646765335F56616C223A312C22534F4C5F457870223A302C22534F4C5F4C76223A312C22534F4C5F4E6578744C76457870223A3130302C22534F4C5F504D5F57696E73223A302C22534F4C5F57696E436861696E4D6178223A302C22534F4C5F57696E436861696E4E6F77223A302C2253656C65637442474D223A302C2253656C6563744368617261223A302C2253656C6563744368617261436F6C6F72223A302C2253656C6563745374616765223A302C22546F74616C506C617954696D65223A33303938393438312C22546F74616C52616E6B4D61746368223A323032302C225570646174655F446179223A31332C225570646174655F486F7572223A31342C225570646174655F4D696E223A31322C225570646174655F4D6F6E7468223A322C225570646174655F59656172223A323032322C22557365724944223A3232303132303031303832323138393937392C22566970436865636B4D61746368223A302C22566970436865636B5074223A302C22566970537461747573223A322C22576F726C64446F6C6C6172223A3430393430302C22576F726C64446F6C6C6172546F74616C223A3530323030302C225A41545F426164676531223A323130332C225A41545F4261646765315F56616C223A392C225A41545F426164676532223A3530343030302C225A41545F4261646765325F56616C223A302C225A41545F426164676533223A3530313030302C225A41545F4261646765335F56616C223A312C225A41545F457870223A302C225A41545F4C76223A312C225A41545F4E6578744C76457870223A3130302C225A41545F504D5F57696E73223A302C225A41545F57696E436861696E4D6178223A302C225A41545F57696E436861696E4E6F77223A307D").unwrap();
-        expect_test::expect_file!["../test_data/vip_response.txt"].assert_debug_eq(&response);
+        let response = Response::<StatisticsResponse>::from_hex(STATISTICS_RESPONSE).unwrap();
+        expect_test::expect_file!["../test_data/statistics_response.txt"]
+            .assert_debug_eq(&response);
     }
 }
@@ -0,0 +1,54 @@
+//! Serialize a Character as its three letter profile code (see Character::to_code()) instead of
+//! its variant name. Use it on a field with `#[serde(with = "ggst_api::serde_code")]`.
+use crate::*;
+
+use serde_crate::{de::Error as _, ser::Error as _, Deserializer, Serializer};
+
+pub fn serialize<S>(character: &Character, serializer: S) -> std::result::Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    match character {
+        Character::Unknown(code) => Err(S::Error::custom(format!(
+            "unknown character {:02x} has no profile code",
+            code
+        ))),
+        c => serializer.serialize_str(c.to_code()),
+    }
+}
+
+pub fn deserialize<'de, D>(deserializer: D) -> std::result::Result<Character, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let code = String::deserialize(deserializer)?;
+    Character::from_code(&code).map_err(D::Error::custom)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    #[serde(crate = "serde_crate")]
+    struct Row {
+        #[serde(with = "super")]
+        character: Character,
+    }
+
+    #[test]
+    fn code_round_trip() {
+        let row = Row {
+            character: Character::HappyChaos,
+        };
+        let json = serde_json::to_string(&row).unwrap();
+        assert_eq!(json, r#"{"character":"COS"}"#);
+        assert_eq!(serde_json::from_str::<Row>(&json).unwrap(), row);
+        assert!(serde_json::from_str::<Row>(r#"{"character":"HappyChaos"}"#).is_err());
+        assert!(serde_json::from_str::<Row>(r#"{"character":"UNK"}"#).is_err());
+        let unknown = Row {
+            character: Character::Unknown(0x16),
+        };
+        assert!(serde_json::to_string(&unknown).is_err());
+    }
+}
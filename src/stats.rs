@@ -0,0 +1,248 @@
+use crate::*;
+
+use std::collections::{HashMap, HashSet};
+
+/// The games and wins of a character against one opponent character, see win_matrix()
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate")
+)]
+pub struct MatchStats {
+    total: usize,
+    wins: usize,
+}
+
+impl MatchStats {
+    /// The number of games played
+    pub fn total(&self) -> usize {
+        self.total
+    }
+
+    /// The number of games won
+    pub fn wins(&self) -> usize {
+        self.wins
+    }
+
+    /// The number of games lost
+    pub fn losses(&self) -> usize {
+        self.total - self.wins
+    }
+
+    /// The share of games won as a fraction between 0.0 and 1.0, not a percentage. Returns 0.0
+    /// without any games.
+    pub fn win_rate(&self) -> f64 {
+        if self.total == 0 {
+            0.0
+        } else {
+            self.wins as f64 / self.total as f64
+        }
+    }
+}
+
+/// The record of the user with id `a` against the user with id `b` as (wins of a, wins of b).
+/// Matches that do not involve both users are ignored.
+pub fn head_to_head(matches: &[Match], a: i64, b: i64) -> (usize, usize) {
+    head_to_head_matches(matches, a, b).fold((0, 0), |(a_wins, b_wins), (m, _, _)| {
+        if m.winner().id == a {
+            (a_wins + 1, b_wins)
+        } else {
+            (a_wins, b_wins + 1)
+        }
+    })
+}
+
+/// Same as head_to_head() but split by the characters played, keyed by (character of a,
+/// character of b).
+pub fn head_to_head_by_character(
+    matches: &[Match],
+    a: i64,
+    b: i64,
+) -> BTreeMap<(Character, Character), (usize, usize)> {
+    let mut records = BTreeMap::new();
+    for (m, a_character, b_character) in head_to_head_matches(matches, a, b) {
+        let record = records.entry((a_character, b_character)).or_insert((0, 0));
+        if m.winner().id == a {
+            record.0 += 1;
+        } else {
+            record.1 += 1;
+        }
+    }
+    records
+}
+
+/// The (winner, loser) characters of every match, e.g. to count the wins of each matchup
+pub fn matchup_outcomes(matches: &[Match]) -> impl Iterator<Item = (Character, Character)> + '_ {
+    matches
+        .iter()
+        .map(|m| (m.winner_character(), m.loser_character()))
+}
+
+/// The record of every character against every opponent character, keyed by (character,
+/// opponent). Every match is counted once from each side, so the stats of (Sol, Ky) and (Ky, Sol)
+/// add up to the same number of games. Mirror matches are excluded since one of the two players
+/// always wins and they would only ever show a win rate of 50%.
+pub fn win_matrix(matches: &[Match]) -> HashMap<(Character, Character), MatchStats> {
+    let mut matrix = HashMap::<_, MatchStats>::new();
+    for (winner, loser) in matchup_outcomes(matches).filter(|(winner, loser)| winner != loser) {
+        let won = matrix.entry((winner, loser)).or_default();
+        won.total += 1;
+        won.wins += 1;
+        matrix.entry((loser, winner)).or_default().total += 1;
+    }
+    matrix
+}
+
+/// The floor of every match the user with id `player_id` played, sorted by time. The floor of a
+/// match is the floor it was played on, which follows the floor of the user.
+pub fn floor_timeline(matches: &[Match], player_id: i64) -> Vec<(DateTime<Utc>, Floor)> {
+    let mut timeline = matches
+        .iter()
+        .filter(|m| m.contains_player(player_id))
+        .map(|m| (*m.timestamp(), m.floor()))
+        .collect::<Vec<_>>();
+    timeline.sort();
+    timeline
+}
+
+/// The floor of the most recent match of the user with id `player_id`, a guess of their current
+/// floor without fetching their profile. None if they are not in any of the matches.
+pub fn current_floor(matches: &[Match], player_id: i64) -> Option<Floor> {
+    matches
+        .iter()
+        .filter(|m| m.contains_player(player_id))
+        .max_by_key(|m| m.timestamp())
+        .map(Match::floor)
+}
+
+/// The fetched matches that are not in `previous`, in the order they were fetched, e.g. to only
+/// store the new matches of a periodic query. Matches are identified by Eq, which ignores player
+/// names, so a player changing their name does not make an old match new. Duplicates within
+/// `fetched` are returned once.
+pub fn new_matches(previous: &HashSet<Match>, fetched: &[Match]) -> Vec<Match> {
+    let mut seen = HashSet::new();
+    fetched
+        .iter()
+        .filter(|m| !previous.contains(*m) && seen.insert(*m))
+        .cloned()
+        .collect()
+}
+
+fn head_to_head_matches(
+    matches: &[Match],
+    a: i64,
+    b: i64,
+) -> impl Iterator<Item = (&Match, Character, Character)> {
+    matches.iter().filter_map(move |m| {
+        let (p1, p2) = m.players();
+        if p1.id == a && p2.id == b {
+            Some((m, p1.character, p2.character))
+        } else if p1.id == b && p2.id == a {
+            Some((m, p2.character, p1.character))
+        } else {
+            None
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::test_match;
+
+    #[test]
+    fn outcomes() {
+        let matches = [
+            test_match((1, Character::Sol), (2, Character::Ky), Winner::Player1),
+            test_match((2, Character::Ky), (1, Character::Sol), Winner::Player1),
+        ];
+        assert_eq!(
+            matchup_outcomes(&matches).collect::<Vec<_>>(),
+            [
+                (Character::Sol, Character::Ky),
+                (Character::Ky, Character::Sol)
+            ]
+        );
+    }
+
+    #[test]
+    fn matrix() {
+        let matches = [
+            test_match((1, Character::Sol), (2, Character::Ky), Winner::Player1),
+            test_match((3, Character::Ky), (4, Character::Sol), Winner::Player1),
+            test_match((5, Character::Sol), (6, Character::Ky), Winner::Player1),
+            test_match((1, Character::Sol), (4, Character::Sol), Winner::Player2),
+        ];
+        let matrix = win_matrix(&matches);
+        assert_eq!(matrix.len(), 2);
+        let sol = matrix[&(Character::Sol, Character::Ky)];
+        assert_eq!(sol, MatchStats { total: 3, wins: 2 });
+        assert_eq!(
+            matrix[&(Character::Ky, Character::Sol)],
+            MatchStats { total: 3, wins: 1 }
+        );
+        assert_eq!(sol.losses(), 1);
+        assert_eq!(sol.win_rate(), 2.0 / 3.0);
+        assert_eq!(MatchStats::default().win_rate(), 0.0);
+    }
+
+    #[test]
+    fn incremental() {
+        let old = test_match((1, Character::Sol), (2, Character::Ky), Winner::Player1);
+        let mut renamed = old.clone();
+        renamed.players.0.name = "renamed".into();
+        let new = test_match((1, Character::Sol), (3, Character::May), Winner::Player2);
+        let previous = HashSet::from([old]);
+        assert_eq!(
+            new_matches(&previous, &[new.clone(), renamed, new.clone()]),
+            [new]
+        );
+    }
+
+    #[test]
+    fn timeline() {
+        let at = |hour| Utc.with_ymd_and_hms(2022, 2, 6, hour, 0, 0).unwrap();
+        let (sol, ky) = ((1, Character::Sol), (2, Character::Ky));
+        let matches = [
+            test_match(sol, ky, Winner::Player1)
+                .at(at(12))
+                .on_floor(Floor::F9),
+            test_match(ky, sol, Winner::Player2)
+                .at(at(10))
+                .on_floor(Floor::F8),
+            test_match((3, Character::May), ky, Winner::Player1)
+                .at(at(11))
+                .on_floor(Floor::F8),
+        ];
+        assert_eq!(
+            floor_timeline(&matches, 1),
+            [(at(10), Floor::F8), (at(12), Floor::F9)]
+        );
+        assert_eq!(current_floor(&matches, 1), Some(Floor::F9));
+        assert_eq!(current_floor(&matches, 3), Some(Floor::F8));
+        assert_eq!(current_floor(&matches, 4), None);
+    }
+
+    #[test]
+    fn rivals() {
+        let matches = [
+            test_match((1, Character::Sol), (2, Character::Ky), Winner::Player1),
+            test_match((2, Character::Ky), (1, Character::Sol), Winner::Player1),
+            test_match((2, Character::May), (1, Character::Sol), Winner::Player2),
+            test_match((1, Character::Sol), (3, Character::Ky), Winner::Player1),
+        ];
+        assert_eq!(head_to_head(&matches, 1, 2), (2, 1));
+        assert_eq!(head_to_head(&matches, 2, 1), (1, 2));
+        assert_eq!(head_to_head(&matches, 2, 3), (0, 0));
+
+        let by_character = head_to_head_by_character(&matches, 1, 2);
+        assert_eq!(
+            by_character.into_iter().collect::<Vec<_>>(),
+            [
+                ((Character::Sol, Character::Ky), (1, 1)),
+                ((Character::Sol, Character::May), (1, 0)),
+            ]
+        );
+    }
+}